@@ -0,0 +1,591 @@
+use crate::neighbourhood::Neighbourhood;
+use crate::pattern::Pattern;
+use crate::rule::Rule;
+use crate::world_state::cycle::Outcome;
+use crate::world_state::sparse_world::{SparseWorld, Viewport};
+use crate::world_state::{CellState, Spawn, WorldState};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+/// The number of generations an object is run alone for, before giving up
+/// on finding its cycle.
+pub const MAX_GENERATIONS: u64 = 1000;
+
+/// The code of the objects which don't settle within `MAX_GENERATIONS`,
+/// such as puffers.
+const UNSETTLED_CODE: &str = "zz";
+
+/// The code of the objects which die.
+const EXTINCT_CODE: &str = "xs0_0";
+
+/// An object of a world: cells close enough to each other to interact,
+/// run alone to find out what it is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Object {
+    /// The cells of the object, at their position in the world.
+    pub pattern: Pattern,
+
+    /// How the object ends when it's run alone, or `None` if it doesn't
+    /// settle within `MAX_GENERATIONS` generations.
+    pub outcome: Option<Outcome>,
+
+    /// The code of the object, the same for all its phases and
+    /// orientations, such as `xp3_.O../.XXO/OXX./..O.`.
+    ///
+    /// As for the apgcodes of apgsearch, it starts with `xs` and the number
+    /// of cells for a still life, `xp` and the period for an oscillator, or
+    /// `xq` and the period for a spaceship. It's followed by the rows of the
+    /// smallest phase, written as `WorldState`'s `Display` does and separated
+    /// by slashes. The objects which die are `xs0_0`, and the ones which
+    /// don't settle are `zz`.
+    ///
+    /// The orientations are the rotations and reflections of the grid which
+    /// map the neighbourhood onto itself: the 8 of the square grid for the
+    /// Moore and von Neumann neighbourhoods, and the 12 of the hexagonal
+    /// grid for the hexagonal one, whose cells are written in the column
+    /// `x - y / 2` (rounded down) instead of `x`, so that an object moved by
+    /// a row keeps its code.
+    pub code: String,
+
+    /// The name of the object in the pattern catalogue, if it's there.
+    pub name: Option<&'static str>,
+}
+
+impl Object {
+    /// Return the speed of the object, such as `c/4 diagonal` or
+    /// `c orthogonal`, `0` if it doesn't move, or `-` if it dies or
+    /// doesn't settle.
+    pub fn velocity(&self) -> String {
+        let Some(Outcome::Cycle { period, dx, dy, .. }) = self.outcome else {
+            return "-".to_string();
+        };
+        let distance = dx.unsigned_abs().max(dy.unsigned_abs());
+        if distance == 0 {
+            return "0".to_string();
+        }
+        let divisor = gcd(distance, period);
+        let (distance, period) = (distance / divisor, period / divisor);
+        let speed = match (distance, period) {
+            (1, 1) => "c".to_string(),
+            (1, _) => format!("c/{period}"),
+            (_, 1) => format!("{distance}c"),
+            _ => format!("{distance}c/{period}"),
+        };
+        let direction = if dx == 0 || dy == 0 {
+            "orthogonal"
+        } else if dx.abs() == dy.abs() {
+            "diagonal"
+        } else {
+            "oblique"
+        };
+        format!("{speed} {direction}")
+    }
+}
+
+/// Return the greatest common divisor of `a` and `b`.
+fn gcd(a: u64, b: u64) -> u64 {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
+}
+
+/// The objects of a world with the same code, counted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    /// The number of objects.
+    pub count: usize,
+
+    /// One of the objects, the others being the same in another phase,
+    /// orientation or position.
+    pub object: Object,
+}
+
+/// The objects of a world, counted by code from the most common one.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Census {
+    /// The objects counted, by decreasing count, then by code.
+    pub entries: Vec<Entry>,
+}
+
+impl fmt::Display for Census {
+    /// Write the census as a table, with a line for each code.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>5}  {:<8}  {:>6}  {:<16}  code",
+            "count", "name", "period", "velocity"
+        )?;
+        for Entry { count, object } in &self.entries {
+            let period = match object.outcome {
+                Some(Outcome::Cycle { period, .. }) => period.to_string(),
+                _ => "-".to_string(),
+            };
+            writeln!(
+                f,
+                "{count:>5}  {:<8}  {period:>6}  {:<16}  {}",
+                object.name.unwrap_or("-"),
+                object.velocity(),
+                object.code
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Split the cells of `ws` which aren't **Dead** into objects: two cells
+/// belong to the same object when they are at most `distance` columns and
+/// `distance` rows apart, directly or through other cells of the object.
+///
+/// The objects are returned from the top of the world, their cells keeping
+/// their position in it. The edges of the world aren't crossed, whatever
+/// its topology.
+pub fn components(ws: &WorldState, distance: usize) -> Vec<Pattern> {
+    let (width, height) = (ws.width(), ws.height());
+    let alive = |x: usize, y: usize| ws.get(x, y) != Ok(CellState::Dead);
+    let mut labelled = vec![false; width * height];
+    let mut components = vec![];
+    for (x, y, cell) in ws.iter() {
+        if cell == CellState::Dead || labelled[y * width + x] {
+            continue;
+        }
+        labelled[y * width + x] = true;
+        let (mut cells, mut pending) = (vec![], vec![(x, y, cell)]);
+        while let Some((x, y, cell)) = pending.pop() {
+            cells.push((x as i64, y as i64, cell));
+            for ny in y.saturating_sub(distance)..(y + distance + 1).min(height) {
+                for nx in x.saturating_sub(distance)..(x + distance + 1).min(width) {
+                    if alive(nx, ny) && !labelled[ny * width + nx] {
+                        labelled[ny * width + nx] = true;
+                        pending.push((nx, ny, ws.get(nx, ny).unwrap()));
+                    }
+                }
+            }
+        }
+        components.push(Pattern::new(cells));
+    }
+    components
+}
+
+/// A rotation or a reflection of a grid, as a map of the coordinates of
+/// its cells.
+type Symmetry = fn(i64, i64) -> (i64, i64);
+
+/// The rotations and reflections of the square grid.
+const SQUARE_SYMMETRIES: [Symmetry; 8] = [
+    |x, y| (x, y),
+    |x, y| (-y, x),
+    |x, y| (-x, -y),
+    |x, y| (y, -x),
+    |x, y| (-x, y),
+    |x, y| (y, x),
+    |x, y| (x, -y),
+    |x, y| (-y, -x),
+];
+
+/// The rotations and reflections of the hexagonal grid, on the axial
+/// coordinates of its cells.
+const HEXAGONAL_SYMMETRIES: [Symmetry; 12] = [
+    |q, r| (q, r),
+    |q, r| (-r, q + r),
+    |q, r| (-q - r, q),
+    |q, r| (-q, -r),
+    |q, r| (r, -q - r),
+    |q, r| (q + r, -q),
+    |q, r| (r, q),
+    |q, r| (-q, q + r),
+    |q, r| (-q - r, r),
+    |q, r| (-r, -q),
+    |q, r| (q, -q - r),
+    |q, r| (q + r, -r),
+];
+
+/// Return the pattern with each cell moved by `f`.
+fn moved(pattern: &Pattern, f: Symmetry) -> Pattern {
+    Pattern::new(
+        pattern
+            .cells()
+            .iter()
+            .map(|&(x, y, cell)| {
+                let (x, y) = f(x, y);
+                (x, y, cell)
+            })
+            .collect(),
+    )
+}
+
+/// Return `pattern` moved to the origin. With the hexagonal neighbourhood,
+/// its cells are first given by their axial coordinates, the column
+/// `x - y / 2` (rounded down) and the row, so that a pattern moved by an odd
+/// number of rows is still the same phase.
+fn phase(pattern: &Pattern, neighbourhood: &Neighbourhood) -> Pattern {
+    let pattern = if *neighbourhood == Neighbourhood::Hexagonal {
+        moved(pattern, |x, y| (x - y.div_euclid(2), y))
+    } else {
+        pattern.clone()
+    };
+    let (x, y) = pattern.corner();
+    pattern.translate(-x, -y)
+}
+
+/// Return the rotations and reflections of the grid of `neighbourhood`
+/// which map it onto itself, on the coordinates `phase()` gives.
+fn symmetries(neighbourhood: &Neighbourhood) -> Vec<Symmetry> {
+    if *neighbourhood == Neighbourhood::Hexagonal {
+        return HEXAGONAL_SYMMETRIES.to_vec();
+    }
+    let sorted = |mut offsets: Vec<(i64, i64)>| {
+        offsets.sort_unstable();
+        offsets
+    };
+    let offsets: Vec<_> = neighbourhood
+        .offsets(false)
+        .into_iter()
+        .map(|(dx, dy)| (dx as i64, dy as i64))
+        .collect();
+    SQUARE_SYMMETRIES
+        .into_iter()
+        .filter(|f| {
+            sorted(offsets.iter().map(|&(dx, dy)| f(dx, dy)).collect()) == sorted(offsets.clone())
+        })
+        .collect()
+}
+
+/// The phases of the cycle of a pattern, and how it ended.
+struct Evolution {
+    /// How the pattern ended, or `None` if it didn't settle.
+    outcome: Option<Outcome>,
+
+    /// The phases of the cycle, as `phase()` gives them.
+    phases: Vec<Pattern>,
+}
+
+/// Run `pattern` alone, in a world without edges following `rule` and
+/// `neighbourhood`, until it repeats itself or dies.
+fn evolve(pattern: &Pattern, rule: &Rule, neighbourhood: &Neighbourhood) -> Evolution {
+    let mut sw = SparseWorld::new(Viewport {
+        x: 0,
+        y: 0,
+        width: 1,
+        height: 1,
+    });
+    sw.set_rule(rule.clone());
+    sw.set_neighbourhood(neighbourhood.clone());
+    for &(x, y, cell) in pattern.cells() {
+        sw.set(x, y, cell);
+    }
+
+    // the generation and the corner of the phases seen, and the phases
+    // in order.
+    let mut seen: HashMap<Pattern, (u64, i64, i64)> = HashMap::new();
+    let mut phases = vec![];
+    for generation in 0..=MAX_GENERATIONS {
+        let current = Pattern::new(sw.cells().collect());
+        if current.cells().is_empty() {
+            return Evolution {
+                outcome: Some(Outcome::Extinct { generation }),
+                phases: vec![],
+            };
+        }
+        let (x, y) = current.corner();
+        let phase = phase(&current, neighbourhood);
+        if let Some(&(transient, x0, y0)) = seen.get(&phase) {
+            return Evolution {
+                outcome: Some(Outcome::Cycle {
+                    transient,
+                    period: generation - transient,
+                    dx: x - x0,
+                    dy: y - y0,
+                }),
+                phases: phases.split_off(transient as usize),
+            };
+        }
+        seen.insert(phase.clone(), (generation, x, y));
+        phases.push(phase);
+        sw.next();
+    }
+    Evolution {
+        outcome: None,
+        phases: vec![],
+    }
+}
+
+/// Return the rows of `pattern`, separated by slashes.
+fn rows(pattern: &Pattern) -> String {
+    let (x, y) = pattern.corner();
+    let width = pattern.cells().iter().map(|&(cx, _, _)| cx - x + 1).max();
+    let height = pattern.cells().iter().map(|&(_, cy, _)| cy - y + 1).max();
+    let mut ws =
+        WorldState::with_dimensions(width.unwrap_or(1) as usize, height.unwrap_or(1) as usize);
+    ws.stamp(&pattern.translate(-x, -y), 0, 0)
+        .expect("the world fits the pattern");
+    ws.to_string().trim_end().replace('\n', "/")
+}
+
+/// Return the code of a pattern which evolved as `evolution` in
+/// `neighbourhood`.
+fn code(evolution: &Evolution, neighbourhood: &Neighbourhood) -> String {
+    let prefix = match evolution.outcome {
+        None => return UNSETTLED_CODE.to_string(),
+        Some(Outcome::Extinct { .. }) => return EXTINCT_CODE.to_string(),
+        Some(Outcome::Cycle {
+            period: 1,
+            dx: 0,
+            dy: 0,
+            ..
+        }) => format!("xs{}", evolution.phases[0].cells().len()),
+        Some(Outcome::Cycle {
+            period,
+            dx: 0,
+            dy: 0,
+            ..
+        }) => format!("xp{period}"),
+        Some(Outcome::Cycle { period, .. }) => format!("xq{period}"),
+    };
+    let symmetries = symmetries(neighbourhood);
+    let body = evolution
+        .phases
+        .iter()
+        .flat_map(|phase| symmetries.iter().map(|&f| rows(&moved(phase, f))))
+        .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+        .expect("a cycle has a phase");
+    format!("{prefix}_{body}")
+}
+
+/// Return the codes of the oscillators and spaceships of the catalogue,
+/// which follow the rule and the neighbourhood of Brian's Brain. They're
+/// only worked out on the first call.
+fn catalogue_codes() -> &'static [(String, &'static str)] {
+    static CODES: OnceLock<Vec<(String, &'static str)>> = OnceLock::new();
+    CODES.get_or_init(|| {
+        Pattern::catalogue()
+            .filter(|name| matches!(Pattern::kind(name), Some("oscillator" | "spaceship")))
+            .map(|name| {
+                let pattern = Pattern::named(name).unwrap();
+                let neighbourhood = Neighbourhood::default();
+                let evolution = evolve(&pattern, &Rule::default(), &neighbourhood);
+                (code(&evolution, &neighbourhood), name)
+            })
+            .collect()
+    })
+}
+
+/// Return the objects of `ws`, split as `components()` does, each one
+/// being run alone to find its code and its name in the catalogue.
+///
+/// The objects are only named with the rule and the neighbourhood of
+/// Brian's Brain, which the patterns of the catalogue follow.
+pub fn objects(ws: &WorldState, distance: usize) -> Vec<Object> {
+    let catalogue = match (ws.rule(), ws.neighbourhood()) {
+        (rule, neighbourhood)
+            if rule == &Rule::default() && neighbourhood == &Neighbourhood::default() =>
+        {
+            catalogue_codes()
+        }
+        _ => &[],
+    };
+    components(ws, distance)
+        .into_iter()
+        .map(|pattern| {
+            let evolution = evolve(&pattern, ws.rule(), ws.neighbourhood());
+            let code = code(&evolution, ws.neighbourhood());
+            let name = catalogue
+                .iter()
+                .find(|(c, _)| c == &code)
+                .map(|&(_, name)| name);
+            Object {
+                pattern,
+                outcome: evolution.outcome,
+                code,
+                name,
+            }
+        })
+        .collect()
+}
+
+/// Return the census of the objects of `ws`, split and classified as
+/// `objects()` does.
+pub fn census(ws: &WorldState, distance: usize) -> Census {
+    let mut entries: Vec<Entry> = vec![];
+    for object in objects(ws, distance) {
+        match entries.iter_mut().find(|e| e.object.code == object.code) {
+            Some(entry) => entry.count += 1,
+            None => entries.push(Entry { count: 1, object }),
+        }
+    }
+    entries.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.object.code.cmp(&b.object.code))
+    });
+    Census { entries }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn placed(placements: &[(&str, i64, i64, u8)], size: usize) -> WorldState {
+        let mut ws = WorldState::new(size);
+        for &(name, x, y, turns) in placements {
+            let pattern = Pattern::named(name).unwrap().rotate(turns);
+            ws.stamp(&pattern, x, y).unwrap();
+        }
+        ws
+    }
+
+    #[test]
+    fn test_components() {
+        let ws = placed(&[("osc3", 0, 0, 0), ("osc3", 8, 0, 0)], 12);
+        let components = components(&ws, 2);
+        assert_eq!(components.len(), 2);
+        assert_eq!(components[0], Pattern::named("osc3").unwrap());
+        assert_eq!(
+            components[1],
+            Pattern::named("osc3").unwrap().translate(8, 0)
+        );
+
+        // the oscillators are 5 columns apart.
+        assert_eq!(super::components(&ws, 4).len(), 2);
+        assert_eq!(super::components(&ws, 5).len(), 1);
+        assert!(super::components(&WorldState::new(3), 2).is_empty());
+    }
+
+    #[test]
+    fn test_objects_are_named() {
+        let ws = placed(
+            &[
+                ("osc3", 0, 0, 0),
+                ("glider", 10, 0, 0),
+                ("glider", 0, 10, 3),
+                ("ship8", 10, 10, 0),
+            ],
+            20,
+        );
+        let objects = objects(&ws, 2);
+        let names: Vec<_> = objects.iter().map(|o| o.name).collect();
+        assert_eq!(
+            names,
+            [Some("osc3"), Some("glider"), Some("glider"), Some("ship8")]
+        );
+        assert!(objects[0].code.starts_with("xp3_"));
+        assert_eq!(objects[1].code, objects[2].code);
+        assert!(objects[1].code.starts_with("xq1_"));
+        assert_eq!(objects[0].velocity(), "0");
+        assert_eq!(objects[1].velocity(), "c orthogonal");
+    }
+
+    #[test]
+    fn test_objects_which_die_or_dont_settle() {
+        let mut ws = placed(&[("wick", 10, 10, 0)], 20);
+        ws.spawn_cell(0, 0, CellState::Alive).unwrap();
+        let objects = objects(&ws, 2);
+        assert_eq!(objects[0].code, EXTINCT_CODE);
+        assert_eq!(objects[0].outcome, Some(Outcome::Extinct { generation: 2 }));
+        assert_eq!(objects[1].code, UNSETTLED_CODE);
+        assert_eq!(objects[1].outcome, None);
+        assert_eq!(objects[1].velocity(), "-");
+    }
+
+    #[test]
+    fn test_still_life_of_another_rule() {
+        let mut ws: WorldState = "OO....\nOO....\n......\n....OO\n....OO\n".parse().unwrap();
+        ws.set_rule("B3/S23".parse().unwrap());
+        let census = census(&ws, 1);
+        assert_eq!(census.entries.len(), 1);
+        assert_eq!(census.entries[0].count, 2);
+        assert_eq!(census.entries[0].object.code, "xs4_OO/OO");
+        assert_eq!(census.entries[0].object.name, None);
+    }
+
+    #[test]
+    fn test_orientations_of_the_hexagonal_grid() {
+        // the cells never die and are never born, so each object is a
+        // still life.
+        let bar = [(0, 0), (1, 0), (2, 0)];
+        let turned_bar = [(0, 0), (0, 1), (1, 2)];
+        let zigzag = [(0, 0), (0, 1), (0, 2)];
+        let world = |neighbourhood| {
+            let mut ws = WorldState::new(20);
+            ws.set_rule("B/S0123456/C2".parse().unwrap());
+            ws.set_neighbourhood(neighbourhood);
+            for (cells, x, y) in [(&bar, 2, 2), (&turned_bar, 10, 2), (&zigzag, 2, 9)] {
+                for &(dx, dy) in cells {
+                    ws.spawn_cell(x + dx, y + dy, CellState::Alive).unwrap();
+                }
+            }
+            // the bar turned by a sixth of a turn, from an odd row.
+            for (x, y) in [(10, 9), (11, 10), (11, 11)] {
+                ws.spawn_cell(x, y, CellState::Alive).unwrap();
+            }
+            ws
+        };
+
+        let codes = |ws| {
+            objects(&ws, 2)
+                .into_iter()
+                .map(|o| o.code)
+                .collect::<Vec<_>>()
+        };
+        let hexagonal = codes(world(Neighbourhood::Hexagonal));
+        assert_eq!(hexagonal[0], "xs3_OOO");
+        assert_eq!(hexagonal[1], hexagonal[0]);
+        assert_ne!(hexagonal[2], hexagonal[0]);
+        assert_eq!(hexagonal[3], hexagonal[0]);
+
+        // the zigzag is a quarter turn of the bar on the square grid.
+        let square = codes(world(Neighbourhood::Moore(1)));
+        assert_eq!(square[2], square[0]);
+        assert_ne!(square[1], square[0]);
+    }
+
+    #[test]
+    fn test_custom_neighbourhoods_keep_their_orientation() {
+        let mut ws = WorldState::new(12);
+        ws.set_rule("B/S012/C2".parse().unwrap());
+        ws.set_neighbourhood(Neighbourhood::Custom(vec![(1, 0)]));
+        for (x, y) in [(1, 1), (2, 1), (1, 6), (1, 7)] {
+            ws.spawn_cell(x, y, CellState::Alive).unwrap();
+        }
+        let objects = objects(&ws, 1);
+        assert_eq!(objects[0].code, "xs2_OO");
+        assert_ne!(objects[1].code, objects[0].code);
+    }
+
+    #[test]
+    fn test_census_of_example1() {
+        let census = census(&WorldState::example1(), 2);
+        assert_eq!(census.entries.len(), 1);
+        assert_eq!(census.entries[0].count, 5);
+        assert_eq!(census.entries[0].object.name, Some("osc3"));
+        assert_eq!(
+            census.to_string(),
+            format!(
+                "count  name      period  velocity          code\n    \
+                 5  osc3           3  0                 {}\n",
+                census.entries[0].object.code
+            )
+        );
+    }
+
+    #[test]
+    fn test_velocity() {
+        let object = |period, dx, dy| Object {
+            pattern: Pattern::new(vec![]),
+            outcome: Some(Outcome::Cycle {
+                transient: 0,
+                period,
+                dx,
+                dy,
+            }),
+            code: String::new(),
+            name: None,
+        };
+        assert_eq!(object(4, 1, -1).velocity(), "c/4 diagonal");
+        assert_eq!(object(6, 0, 4).velocity(), "2c/3 orthogonal");
+        assert_eq!(object(2, 4, 2).velocity(), "2c oblique");
+    }
+}
@@ -0,0 +1,59 @@
+use crate::world_state::WorldState;
+
+use std::error::Error;
+use std::path::Path;
+
+/// Module containing the Macrocell format (`.mc`), used by Golly to
+/// store huge patterns.
+pub mod macrocell;
+
+/// Module containing the plaintext format (`.cells`), in which each cell
+/// is a character.
+pub mod plaintext;
+
+/// Module containing the Run Length Encoded format (`.rle`), used by
+/// Golly and the LifeWiki to share patterns.
+pub mod rle;
+
+/// The file formats in which a **WorldState** can be stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Run Length Encoded, `.rle`.
+    Rle,
+
+    /// Plaintext, `.cells`.
+    Plaintext,
+
+    /// Macrocell, `.mc`.
+    Macrocell,
+}
+
+impl Format {
+    /// Return the format of the file at `path`, guessed from its extension.
+    /// Files with an unknown extension are considered to be RLE files.
+    pub fn of(path: &Path) -> Format {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("cells") => Format::Plaintext,
+            Some("mc") => Format::Macrocell,
+            _ => Format::Rle,
+        }
+    }
+
+    /// Read a **WorldState** from the content of a file in this format.
+    pub fn parse(self, s: &str) -> Result<WorldState, Box<dyn Error>> {
+        Ok(match self {
+            Format::Rle => rle::parse(s)?,
+            Format::Plaintext => plaintext::parse(s)?,
+            Format::Macrocell => macrocell::parse(s)?,
+        })
+    }
+
+    /// Write a **WorldState** in this format.
+    pub fn write(self, ws: &WorldState) -> String {
+        match self {
+            Format::Rle => rle::write(ws),
+            Format::Plaintext => plaintext::write(ws),
+            Format::Macrocell => macrocell::write(ws),
+        }
+    }
+}
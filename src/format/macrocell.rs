@@ -0,0 +1,489 @@
+use crate::rule::{ParseRuleError, Rule};
+use crate::world_state::{CellState, WorldState};
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+/// The first line of a Macrocell file.
+const HEADER: &str = "[M2]";
+
+/// The level of the leaves of two-state rules, written as 8x8 cells.
+const BIT_LEAF_LEVEL: u8 = 3;
+
+/// The highest level of a node, so that the coordinates fit in a `u64`.
+const MAX_LEVEL: u8 = 62;
+
+/// The reasons why a Macrocell file can't be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MacrocellError {
+    /// The file doesn't start with `[M2]`.
+    Header(String),
+
+    /// The rule given by `#R` can't be parsed.
+    Rule(ParseRuleError),
+
+    /// The node on this line (counted from 1) is malformed, refers to a
+    /// node which isn't defined before it or has a state outside the rule.
+    Node(usize),
+
+    /// The file doesn't define any node.
+    Empty,
+
+    /// The pattern has too many cells to be stored in a **WorldState**.
+    TooLarge,
+
+    /// A cell lies outside of the dimensions given by `#C x = ..., y = ...`.
+    Overflow {
+        /// The column of the cell.
+        x: u64,
+        /// The row of the cell.
+        y: u64,
+    },
+}
+
+impl fmt::Display for MacrocellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MacrocellError::Header(line) => write!(f, "`{line}` isn't the header `{HEADER}`"),
+            MacrocellError::Rule(e) => write!(f, "the rule given by `#R` is invalid: {e}"),
+            MacrocellError::Node(line) => write!(f, "the node on line {line} is invalid"),
+            MacrocellError::Empty => write!(f, "the file doesn't define any node"),
+            MacrocellError::TooLarge => write!(f, "the pattern is too large to be loaded"),
+            MacrocellError::Overflow { x, y } => {
+                write!(f, "the cell ({x}, {y}) is outside of the pattern")
+            }
+        }
+    }
+}
+
+impl Error for MacrocellError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MacrocellError::Rule(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// A node of the quadtree of a Macrocell file.
+enum Node {
+    /// The cells which aren't **Dead** in a leaf, relative to its top left
+    /// corner, and the level of the leaf.
+    Leaf(u8, Vec<(u64, u64, CellState)>),
+
+    /// The level of the node, and its north-west, north-east, south-west
+    /// and south-east children (0 for an empty child).
+    Inner(u8, [usize; 4]),
+}
+
+impl Node {
+    fn level(&self) -> u8 {
+        match self {
+            Node::Leaf(level, _) | Node::Inner(level, _) => *level,
+        }
+    }
+}
+
+/// Read a **WorldState** from the content of a Macrocell (`.mc`) file.
+///
+/// The world follows the rule given by `#R` (Brian's Brain if there's
+/// none). It has the dimensions given by a comment `#C x = ..., y = ...`,
+/// the pattern being placed at its top left corner. Without them, the
+/// world is cropped to the cells of the pattern which aren't **Dead**.
+///
+/// The leaves of the quadtree are written either as 8x8 cells (`.` for
+/// **Dead**, `*` for **Alive**, `$` ending a row), or as level 1 nodes
+/// whose four children are states (0 for **Dead**, 1 for **Alive**, and
+/// the next ones for the stages of **Dying**).
+pub fn parse(s: &str) -> Result<WorldState, MacrocellError> {
+    let mut lines = s.lines().map(str::trim).enumerate();
+    let header = lines.next().map_or("", |(_, line)| line);
+    if !header.starts_with(HEADER) {
+        return Err(MacrocellError::Header(header.to_string()));
+    }
+
+    let (mut rule, mut dimensions) = (Rule::default(), None);
+    // the node 0 stands for any empty node.
+    let mut nodes = vec![Node::Leaf(0, vec![])];
+    for (i, line) in lines {
+        let node_error = || MacrocellError::Node(i + 1);
+        if let Some(r) = line.strip_prefix("#R") {
+            rule = r.trim().parse().map_err(MacrocellError::Rule)?;
+        } else if let Some(comment) = line.strip_prefix("#C") {
+            dimensions = dimensions.or_else(|| parse_dimensions(comment));
+        } else if line.starts_with('#') || line.is_empty() {
+            continue;
+        } else if line.starts_with(['.', '*', '$']) {
+            nodes.push(parse_bit_leaf(line).ok_or_else(node_error)?);
+        } else {
+            let numbers: Vec<usize> = line
+                .split_whitespace()
+                .map(|n| n.parse().map_err(|_| node_error()))
+                .collect::<Result<_, _>>()?;
+            let &[level, nw, ne, sw, se] = numbers.as_slice() else {
+                return Err(node_error());
+            };
+            let children = [nw, ne, sw, se];
+            let node = match level {
+                1 => {
+                    let cells = children
+                        .iter()
+                        .zip([(0, 0), (1, 0), (0, 1), (1, 1)])
+                        .filter(|(&state, _)| state != 0)
+                        .map(|(&state, (x, y))| {
+                            let cell = match state {
+                                1 => CellState::Alive,
+                                n if n - 2 < rule.dying_stages() as usize => {
+                                    CellState::Dying((n - 2) as u8)
+                                }
+                                _ => return Err(node_error()),
+                            };
+                            Ok((x, y, cell))
+                        })
+                        .collect::<Result<_, _>>()?;
+                    Node::Leaf(1, cells)
+                }
+                _ if (2..=MAX_LEVEL as usize).contains(&level) => {
+                    let defined = |&c: &usize| {
+                        c == 0 || (c < nodes.len() && nodes[c].level() as usize == level - 1)
+                    };
+                    if !children.iter().all(defined) {
+                        return Err(node_error());
+                    }
+                    Node::Inner(level as u8, children)
+                }
+                _ => return Err(node_error()),
+            };
+            nodes.push(node);
+        }
+    }
+    if nodes.len() == 1 {
+        return Err(MacrocellError::Empty);
+    }
+
+    let bounds = bounds(&nodes);
+    let root = nodes.len() - 1;
+    let (x0, y0, width, height) = match (dimensions, bounds[root]) {
+        (Some((width, height)), _) => (0, 0, width, height),
+        (None, None) => (0, 0, 0, 0),
+        (None, Some((x0, y0, x1, y1))) => (x0, y0, x1 - x0 + 1, y1 - y0 + 1),
+    };
+    if width
+        .checked_mul(height)
+        .is_none_or(|n| n >= u32::MAX as u64)
+    {
+        return Err(MacrocellError::TooLarge);
+    }
+
+    let mut ws = WorldState::with_dimensions(width as usize, height as usize);
+    ws.set_rule(rule);
+    paint(&nodes, &bounds, root, 0, 0, &mut |x, y, cell| {
+        let (x, y) = (x - x0, y - y0);
+        ws.set(x as usize, y as usize, cell)
+            .map_err(|_| MacrocellError::Overflow { x, y })
+    })?;
+    Ok(ws)
+}
+
+/// Parse the dimensions of a comment `x = ..., y = ...`.
+fn parse_dimensions(comment: &str) -> Option<(u64, u64)> {
+    let (x, y) = comment.split_once(',')?;
+    let (x_key, width) = x.split_once('=')?;
+    let (y_key, height) = y.split_once('=')?;
+    if x_key.trim() != "x" || y_key.trim() != "y" {
+        return None;
+    }
+    Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+}
+
+/// Parse a leaf of 8x8 cells, written row by row.
+fn parse_bit_leaf(line: &str) -> Option<Node> {
+    let side = 1 << BIT_LEAF_LEVEL;
+    let (mut x, mut y, mut cells) = (0, 0, vec![]);
+    for c in line.chars() {
+        match c {
+            '$' => (x, y) = (0, y + 1),
+            '.' | '*' if x < side && y < side => {
+                if c == '*' {
+                    cells.push((x, y, CellState::Alive));
+                }
+                x += 1;
+            }
+            _ => return None,
+        }
+    }
+    Some(Node::Leaf(BIT_LEAF_LEVEL, cells))
+}
+
+/// The leftmost column, the topmost row, the rightmost column and the
+/// bottommost row of the cells which aren't **Dead** in a node, relative to
+/// its top left corner, or `None` if they are all **Dead**.
+type Bounds = Option<(u64, u64, u64, u64)>;
+
+/// Return the bounds of each node, worked out from the ones of its
+/// children, which come before it.
+fn bounds(nodes: &[Node]) -> Vec<Bounds> {
+    let union = |a: Bounds, b: Bounds| match (a, b) {
+        (Some(a), Some(b)) => Some((a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3))),
+        _ => a.or(b),
+    };
+    let mut bounds: Vec<Bounds> = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        bounds.push(match node {
+            Node::Leaf(_, cells) => cells
+                .iter()
+                .fold(None, |b, &(x, y, _)| union(b, Some((x, y, x, y)))),
+            Node::Inner(level, children) => {
+                let half = 1 << (level - 1);
+                let corners = [(0, 0), (half, 0), (0, half), (half, half)];
+                children
+                    .iter()
+                    .zip(corners)
+                    .fold(None, |b, (&c, (cx, cy))| {
+                        let moved =
+                            bounds[c].map(|(x0, y0, x1, y1)| (x0 + cx, y0 + cy, x1 + cx, y1 + cy));
+                        union(b, moved)
+                    })
+            }
+        });
+    }
+    bounds
+}
+
+/// Call `set` with the column, row and state of each cell which isn't
+/// **Dead** in the node `id`, whose top left corner is at (`x`, `y`),
+/// stopping at the first error. The empty nodes, given by `bounds`, are
+/// skipped.
+fn paint(
+    nodes: &[Node],
+    bounds: &[Bounds],
+    id: usize,
+    x: u64,
+    y: u64,
+    set: &mut impl FnMut(u64, u64, CellState) -> Result<(), MacrocellError>,
+) -> Result<(), MacrocellError> {
+    match &nodes[id] {
+        Node::Leaf(_, leaf) => {
+            for &(dx, dy, cell) in leaf {
+                set(x + dx, y + dy, cell)?;
+            }
+        }
+        Node::Inner(level, children) => {
+            let half = 1 << (level - 1);
+            let corners = [(x, y), (x + half, y), (x, y + half), (x + half, y + half)];
+            for (&child, (cx, cy)) in children.iter().zip(corners) {
+                if bounds[child].is_some() {
+                    paint(nodes, bounds, child, cx, cy, set)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Builds the lines of the nodes of a Macrocell file, each distinct node
+/// being written once.
+struct Writer<'a> {
+    rows: Vec<&'a [CellState]>,
+    two_states: bool,
+    lines: Vec<String>,
+    ids: HashMap<String, usize>,
+}
+
+impl Writer<'_> {
+    /// Return the state of the cell at (`x`, `y`), **Dead** outside the world.
+    fn get(&self, x: usize, y: usize) -> CellState {
+        match self.rows.get(y).and_then(|row| row.get(x)) {
+            Some(&cell) => cell,
+            None => CellState::Dead,
+        }
+    }
+
+    /// Return the id of `line`, writing it if it's a new node.
+    fn id(&mut self, line: String) -> usize {
+        if let Some(&id) = self.ids.get(&line) {
+            return id;
+        }
+        self.lines.push(line.clone());
+        self.ids.insert(line, self.lines.len());
+        self.lines.len()
+    }
+
+    /// Write the node of `level` whose top left corner is at (`x`, `y`),
+    /// and return its id (0 if it's empty).
+    fn node(&mut self, x: usize, y: usize, level: u8) -> usize {
+        if self.two_states && level == BIT_LEAF_LEVEL {
+            let side = 1 << BIT_LEAF_LEVEL;
+            let rows: Vec<String> = (0..side)
+                .map(|dy| {
+                    let row: String = (0..side)
+                        .map(|dx| match self.get(x + dx, y + dy) {
+                            CellState::Alive => '*',
+                            _ => '.',
+                        })
+                        .collect();
+                    format!("{}$", row.trim_end_matches('.'))
+                })
+                .collect();
+            let Some(last) = rows.iter().rposition(|row| row != "$") else {
+                return 0;
+            };
+            return self.id(rows[..=last].concat());
+        }
+
+        let half = 1 << (level - 1);
+        let corners = [(x, y), (x + half, y), (x, y + half), (x + half, y + half)];
+        let children = corners.map(|(cx, cy)| match level {
+            1 => match self.get(cx, cy) {
+                CellState::Dead => 0,
+                CellState::Alive => 1,
+                CellState::Dying(stage) => stage as usize + 2,
+            },
+            _ => self.node(cx, cy, level - 1),
+        });
+        if children == [0; 4] {
+            return 0;
+        }
+        let [nw, ne, sw, se] = children;
+        self.id(format!("{level} {nw} {ne} {sw} {se}"))
+    }
+}
+
+/// Write a **WorldState** in the Macrocell format, with its rule and its
+/// dimensions. The leaves are written as 8x8 cells for two-state rules,
+/// and as level 1 nodes for the other ones.
+pub fn write(ws: &WorldState) -> String {
+    let two_states = ws.rule().dying_stages() == 0;
+    let leaf_level = if two_states { BIT_LEAF_LEVEL } else { 1 };
+    let side = ws
+        .width()
+        .max(ws.height())
+        .max(1 << leaf_level)
+        .next_power_of_two();
+    let level = side.trailing_zeros() as u8;
+
+    let mut writer = Writer {
+        rows: ws.rows().collect(),
+        two_states,
+        lines: vec![],
+        ids: HashMap::new(),
+    };
+    if writer.node(0, 0, level) == 0 {
+        writer.lines.push(format!("{level} 0 0 0 0"));
+    }
+
+    let mut s = format!(
+        "{HEADER} (brian-s-brain)\n#R {}\n#C x = {}, y = {}\n",
+        ws.rule(),
+        ws.width(),
+        ws.height()
+    );
+    for line in writer.lines {
+        s.push_str(&line);
+        s.push('\n');
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::world_state::Spawn;
+
+    #[test]
+    fn test_round_trip_examples() {
+        for ws in [
+            WorldState::example1(),
+            WorldState::example2(),
+            WorldState::example3(),
+        ] {
+            assert_eq!(parse(&write(&ws)), Ok(ws));
+        }
+    }
+
+    #[test]
+    fn test_round_trip_two_states() {
+        let mut ws = WorldState::with_dimensions(20, 9);
+        ws.set_rule("B3/S23".parse().unwrap());
+        for (x, y) in [(4, 3), (5, 4), (3, 5), (4, 5), (5, 5), (19, 8)] {
+            ws.spawn_cell(x, y, CellState::Alive).unwrap();
+        }
+        let mc = write(&ws);
+        assert!(mc.lines().any(|line| line.ends_with('$')));
+        assert_eq!(parse(&mc), Ok(ws));
+    }
+
+    #[test]
+    fn test_round_trip_empty_world() {
+        let ws = WorldState::with_dimensions(3, 5);
+        assert_eq!(parse(&write(&ws)), Ok(ws));
+    }
+
+    #[test]
+    fn test_identical_nodes_are_written_once() {
+        // example1 is made of 5 copies of the same oscillator.
+        let mc = write(&WorldState::example1());
+        let nodes: Vec<&str> = mc.lines().filter(|l| !l.starts_with(['[', '#'])).collect();
+        let mut distinct = nodes.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        assert_eq!(nodes.len(), distinct.len());
+    }
+
+    #[test]
+    fn test_parse_golly_file_without_dimensions() {
+        let mc = "[M2] (golly 4.0)\n#R B3/S23\n$$..*$...*$.***$\n4 0 0 0 1\n";
+        let ws = parse(mc).unwrap();
+        assert_eq!(ws.to_string(), ".O.\n..O\nOOO\n");
+        assert_eq!(ws.rule(), &"B3/S23".parse().unwrap());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("x = 1, y = 1\n"),
+            Err(MacrocellError::Header("x = 1, y = 1".to_string()))
+        );
+        assert_eq!(parse("[M2]\n#R B2/S/C3\n"), Err(MacrocellError::Empty));
+        assert_eq!(
+            parse("[M2]\n#R B2\n"),
+            Err(MacrocellError::Rule(ParseRuleError::Format(
+                "B2".to_string()
+            )))
+        );
+        assert_eq!(parse("[M2]\n1 0 3 0 0\n"), Err(MacrocellError::Node(2)));
+        assert_eq!(
+            parse("[M2]\n1 0 1 0 0\n3 1 0 0 0\n"),
+            Err(MacrocellError::Node(3))
+        );
+        assert_eq!(parse("[M2]\n2 1 0 0 0\n"), Err(MacrocellError::Node(2)));
+        assert_eq!(parse("[M2]\n.........*$\n"), Err(MacrocellError::Node(2)));
+        assert_eq!(
+            parse("[M2]\n#C x = 1, y = 1\n1 0 1 0 0\n"),
+            Err(MacrocellError::Overflow { x: 1, y: 0 })
+        );
+    }
+
+    #[test]
+    fn test_huge_patterns_are_rejected_before_being_painted() {
+        // two cells at opposite corners of a node of level 62, and a node of
+        // level 62 whose 4^62 cells, all alive, would fill the memory if
+        // they were painted.
+        let mut corners = "[M2]\n1 1 0 0 0\n1 0 0 0 1\n2 1 0 0 2\n".to_string();
+        let mut full = "[M2]\n1 1 1 1 1\n".to_string();
+        for level in 2..=62 {
+            if level > 2 {
+                corners += &format!("{level} {level} 0 0 {level}\n");
+            }
+            full += &format!("{level} {0} {0} {0} {0}\n", level - 1);
+        }
+        assert_eq!(parse(&corners), Err(MacrocellError::TooLarge));
+        assert_eq!(parse(&full), Err(MacrocellError::TooLarge));
+
+        // the same cells, in a world which is too small for them.
+        let full = full.replacen("[M2]\n", "[M2]\n#C x = 3, y = 3\n", 1);
+        assert_eq!(parse(&full), Err(MacrocellError::Overflow { x: 3, y: 0 }));
+    }
+}
@@ -0,0 +1,122 @@
+use crate::rule::{ParseRuleError, Rule};
+use crate::world_state::{ParseWorldError, WorldState};
+
+use std::error::Error;
+use std::fmt;
+
+/// The comment giving the rule of the pattern.
+const RULE_COMMENT: &str = "!Rule:";
+
+/// The reasons why a plaintext file can't be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PlaintextError {
+    /// The rule given in the comments can't be parsed.
+    Rule(ParseRuleError),
+
+    /// The cells can't be parsed.
+    World(ParseWorldError),
+}
+
+impl fmt::Display for PlaintextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlaintextError::Rule(e) => write!(f, "the rule of the comments is invalid: {e}"),
+            PlaintextError::World(e) => write!(f, "the cells are invalid: {e}"),
+        }
+    }
+}
+
+impl Error for PlaintextError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PlaintextError::Rule(e) => Some(e),
+            PlaintextError::World(e) => Some(e),
+        }
+    }
+}
+
+/// Read a **WorldState** from the content of a plaintext (`.cells`) file.
+///
+/// The lines starting with `!` are comments, `!Rule: ...` giving the rule
+/// of the pattern (Brian's Brain if there's none). The other lines are the
+/// rows of the pattern, shown as `WorldState`'s `Display` does, `*` being
+/// accepted for **Alive** as well. The rows shorter than the longest one
+/// are completed with **Dead** cells.
+pub fn parse(s: &str) -> Result<WorldState, PlaintextError> {
+    let mut rule = Rule::default();
+    let mut rows = vec![];
+    for line in s.lines().map(|line| line.trim_end_matches('\r')) {
+        match line.strip_prefix(RULE_COMMENT) {
+            Some(comment) => rule = comment.trim().parse().map_err(PlaintextError::Rule)?,
+            None if line.starts_with('!') => {}
+            None => rows.push(line.replace('*', "O")),
+        }
+    }
+
+    let width = rows
+        .iter()
+        .map(|row| row.chars().count())
+        .max()
+        .unwrap_or(0);
+    let cells: String = rows.iter().map(|row| format!("{row:.<width$}\n")).collect();
+    let mut ws: WorldState = cells.parse().map_err(PlaintextError::World)?;
+    ws.set_rule(rule);
+    Ok(ws)
+}
+
+/// Write a **WorldState** in the plaintext format, its rule being given in
+/// a comment. The stages of **Dying** beyond `z` can't be written.
+pub fn write(ws: &WorldState) -> String {
+    format!("{RULE_COMMENT} {}\n{ws}", ws.rule())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::world_state::{CellState, Spawn};
+
+    #[test]
+    fn test_round_trip_examples() {
+        for ws in [
+            WorldState::example1(),
+            WorldState::example2(),
+            WorldState::example3(),
+        ] {
+            assert_eq!(parse(&write(&ws)), Ok(ws));
+        }
+    }
+
+    #[test]
+    fn test_round_trip_rule() {
+        let mut ws = WorldState::with_dimensions(3, 2);
+        ws.set_rule("B2/S345/C4".parse().unwrap());
+        ws.spawn_cell(0, 1, CellState::Dying(1)).unwrap();
+        let cells = write(&ws);
+        assert_eq!(cells, "!Rule: B2/S345/C4\n...\na..\n");
+        assert_eq!(parse(&cells), Ok(ws));
+    }
+
+    #[test]
+    fn test_parse_cells_file_from_the_wiki() {
+        let cells = "!Name: Glider\n!\n.O\n..O\nOOO\n";
+        let ws = parse(cells).unwrap();
+        assert_eq!(ws.to_string(), ".O.\n..O\nOOO\n");
+        assert_eq!(ws.rule(), &Rule::default());
+        assert_eq!(parse("*.*\n").unwrap().to_string(), "O.O\n");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("!Rule: B2\n.O\n"),
+            Err(PlaintextError::Rule(ParseRuleError::Format(
+                "B2".to_string()
+            )))
+        );
+        assert_eq!(
+            parse(".O\nO?\n"),
+            Err(PlaintextError::World(ParseWorldError::Cell('?')))
+        );
+    }
+}
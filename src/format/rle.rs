@@ -0,0 +1,397 @@
+use crate::rule::{ParseRuleError, Rule};
+use crate::world_state::{CellState, ConfigError, OutOfBounds, WorldState};
+
+use std::error::Error;
+use std::fmt;
+
+/// The maximum number of characters on a line of an RLE file.
+const LINE_LENGTH: usize = 70;
+
+/// The number of states written with a single uppercase letter.
+const LETTERS: u16 = 24;
+
+/// The reasons why an RLE file can't be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RleError {
+    /// The header line `x = ..., y = ..., rule = ...` is missing or malformed.
+    Header(String),
+
+    /// The rule of the header can't be parsed.
+    Rule(ParseRuleError),
+
+    /// The dimensions of the header give a pattern without any cell.
+    Empty,
+
+    /// The dimensions of the header give more cells than a world can have.
+    TooLarge {
+        /// The number of columns of the header.
+        width: usize,
+        /// The number of rows of the header.
+        height: usize,
+    },
+
+    /// A count is too large to be a number of cells.
+    Count,
+
+    /// A character of the pattern isn't a count, a state, `$` or `!`.
+    Tag(char),
+
+    /// A state doesn't exist in the rule of the pattern.
+    State(String),
+
+    /// A cell lies outside of the dimensions given by the header.
+    Overflow {
+        /// The column of the cell.
+        x: usize,
+        /// The row of the cell.
+        y: usize,
+    },
+
+    /// The pattern doesn't end with `!`.
+    Unterminated,
+}
+
+impl fmt::Display for RleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RleError::Header(line) => {
+                write!(f, "`{line}` isn't a header `x = ..., y = ..., rule = ...`")
+            }
+            RleError::Rule(e) => write!(f, "the rule of the header is invalid: {e}"),
+            RleError::Empty => write!(f, "the pattern has no cell"),
+            RleError::TooLarge { width, height } => {
+                write!(f, "a pattern of {width}x{height} cells is too large")
+            }
+            RleError::Count => write!(f, "a count is too large to be a number of cells"),
+            RleError::Tag(c) => write!(f, "`{c}` isn't a count, a state, `$` or `!`"),
+            RleError::State(state) => {
+                write!(f, "the state `{state}` doesn't exist in the rule")
+            }
+            RleError::Overflow { x, y } => {
+                write!(f, "the cell ({x}, {y}) is outside of the pattern")
+            }
+            RleError::Unterminated => write!(f, "the pattern doesn't end with `!`"),
+        }
+    }
+}
+
+impl Error for RleError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RleError::Rule(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseRuleError> for RleError {
+    fn from(e: ParseRuleError) -> RleError {
+        RleError::Rule(e)
+    }
+}
+
+impl From<ConfigError> for RleError {
+    /// Only the errors about the dimensions of a world are expected.
+    fn from(e: ConfigError) -> RleError {
+        match e {
+            ConfigError::TooLarge { width, height } => RleError::TooLarge { width, height },
+            _ => RleError::Empty,
+        }
+    }
+}
+
+impl From<OutOfBounds> for RleError {
+    fn from(e: OutOfBounds) -> RleError {
+        RleError::Overflow {
+            x: e.x as usize,
+            y: e.y as usize,
+        }
+    }
+}
+
+/// Read a **WorldState** from the content of an RLE file.
+///
+/// The world has the dimensions given by the header, and follows its rule
+/// if there's one. The states are written `b` or `.` for **Dead**, `o` or
+/// `A` for **Alive**, and `B`, `C`, ... for the stages of **Dying**, as
+/// Golly does for the rules of the *Generations* family. The lines
+/// starting with `#` before the header are ignored.
+pub fn parse(s: &str) -> Result<WorldState, RleError> {
+    let mut lines = s
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| line.is_empty() || line.starts_with('#'));
+    let header = lines.next().ok_or(RleError::Header(String::new()))?;
+    let (width, height, rule) = parse_header(header)?;
+
+    let mut ws = WorldState::try_with_dimensions(width, height)?;
+    ws.set_rule(rule.clone());
+
+    let (mut x, mut y) = (0, 0usize);
+    let mut count: Option<usize> = None;
+    let mut chars = lines.flat_map(str::chars).filter(|c| !c.is_whitespace());
+    while let Some(c) = chars.next() {
+        let state = match c {
+            '0'..='9' => {
+                let digit = c.to_digit(10).unwrap() as usize;
+                let n = count.unwrap_or(0).checked_mul(10);
+                count = Some(
+                    n.and_then(|n| n.checked_add(digit))
+                        .ok_or(RleError::Count)?,
+                );
+                continue;
+            }
+            '!' => return Ok(ws),
+            '$' => {
+                y = y.saturating_add(count.take().unwrap_or(1));
+                x = 0;
+                continue;
+            }
+            'b' | '.' => 0,
+            'o' => 1,
+            'A'..='X' => state_of(None, c),
+            'p'..='y' => match chars.next() {
+                Some(letter @ 'A'..='X') => state_of(Some(c), letter),
+                Some(letter) => return Err(RleError::State(format!("{c}{letter}"))),
+                None => return Err(RleError::Unterminated),
+            },
+            _ => return Err(RleError::Tag(c)),
+        };
+
+        let cell = match state {
+            0 => CellState::Dead,
+            1 => CellState::Alive,
+            n if n - 2 < rule.dying_stages() as u16 => CellState::Dying((n - 2) as u8),
+            _ => return Err(RleError::State(tag_of(state))),
+        };
+        let run = count.take().unwrap_or(1);
+        if run > width - x || y >= height {
+            let x = if y < height { x.max(width) } else { x };
+            return Err(RleError::Overflow { x, y });
+        }
+        if cell != CellState::Dead {
+            for i in x..x + run {
+                ws.set(i, y, cell)?;
+            }
+        }
+        x += run;
+    }
+    Err(RleError::Unterminated)
+}
+
+/// Parse the header of an RLE file, made of the dimensions of the pattern
+/// and its rule (`B2/S/C3` if it isn't given).
+fn parse_header(line: &str) -> Result<(usize, usize, Rule), RleError> {
+    let header_error = || RleError::Header(line.to_string());
+    let (mut width, mut height, mut rule) = (None, None, Rule::default());
+    for field in line.split(',') {
+        let (key, value) = field.split_once('=').ok_or_else(header_error)?;
+        match key.trim() {
+            "x" => width = Some(value.trim().parse().map_err(|_| header_error())?),
+            "y" => height = Some(value.trim().parse().map_err(|_| header_error())?),
+            "rule" => rule = value.trim().parse()?,
+            _ => {}
+        }
+    }
+    match (width, height) {
+        (Some(width), Some(height)) => Ok((width, height, rule)),
+        _ => Err(header_error()),
+    }
+}
+
+/// Return the state written with the uppercase `letter`, preceded by the
+/// lowercase `prefix` for the states beyond the 24th.
+fn state_of(prefix: Option<char>, letter: char) -> u16 {
+    let block = prefix.map_or(0, |p| p as u16 - 'p' as u16 + 1);
+    block * LETTERS + (letter as u16 - 'A' as u16) + 1
+}
+
+/// Return how `state` is written, the reverse of `state_of()`.
+fn tag_of(state: u16) -> String {
+    let (block, letter) = ((state - 1) / LETTERS, (state - 1) % LETTERS);
+    let letter = (b'A' + letter as u8) as char;
+    match block {
+        0 => letter.to_string(),
+        _ => format!("{}{letter}", (b'p' + block as u8 - 1) as char),
+    }
+}
+
+/// Write a **WorldState** in the RLE format, with its dimensions and rule.
+///
+/// Life-like rules use `b` and `o` for the states, the other ones use
+/// the letters of the *Generations* family.
+pub fn write(ws: &WorldState) -> String {
+    let two_states = ws.rule().dying_stages() == 0;
+    let tag = |cell: CellState| match (cell, two_states) {
+        (CellState::Dead, true) => "b".to_string(),
+        (CellState::Alive, true) => "o".to_string(),
+        (CellState::Dead, false) => ".".to_string(),
+        (CellState::Alive, false) => tag_of(1),
+        (CellState::Dying(stage), _) => tag_of(stage as u16 + 2),
+    };
+    let run = |count: usize, tag: &str| match count {
+        1 => tag.to_string(),
+        _ => format!("{count}{tag}"),
+    };
+
+    // the runs of the pattern, the dead cells ending a row and the empty
+    // rows ending the pattern being left out.
+    let mut runs = vec![];
+    let mut pending_rows = 0;
+    for row in ws.rows() {
+        let end = row.iter().rposition(|&c| c != CellState::Dead);
+        let Some(end) = end else {
+            pending_rows += 1;
+            continue;
+        };
+        if pending_rows > 0 {
+            runs.push(run(pending_rows, "$"));
+        }
+        let mut cells = row[..=end].iter().peekable();
+        while let Some(&cell) = cells.next() {
+            let mut count = 1;
+            while cells.next_if(|&&c| c == cell).is_some() {
+                count += 1;
+            }
+            runs.push(run(count, &tag(cell)));
+        }
+        pending_rows = 1;
+    }
+    runs.push("!".to_string());
+
+    let mut s = format!(
+        "x = {}, y = {}, rule = {}\n",
+        ws.width(),
+        ws.height(),
+        ws.rule()
+    );
+    let mut line_length = 0;
+    for run in runs {
+        if line_length + run.len() > LINE_LENGTH {
+            s.push('\n');
+            line_length = 0;
+        }
+        line_length += run.len();
+        s.push_str(&run);
+    }
+    s.push('\n');
+    s
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::world_state::Spawn;
+
+    #[test]
+    fn test_round_trip_examples() {
+        for ws in [
+            WorldState::example1(),
+            WorldState::example2(),
+            WorldState::example3(),
+        ] {
+            assert_eq!(parse(&write(&ws)), Ok(ws));
+        }
+    }
+
+    #[test]
+    fn test_round_trip_dying_stages() {
+        let mut ws = WorldState::with_dimensions(40, 3);
+        ws.set_rule("B2/S/C40".parse().unwrap());
+        for stage in 0..38 {
+            ws.spawn_cell(stage, 1, CellState::Dying(stage as u8))
+                .unwrap();
+        }
+        ws.spawn_cell(39, 2, CellState::Alive).unwrap();
+        let rle = write(&ws);
+        assert!(rle.contains("XpA"));
+        assert!(rle.lines().all(|line| line.len() <= LINE_LENGTH));
+        assert_eq!(parse(&rle), Ok(ws));
+    }
+
+    #[test]
+    fn test_write() {
+        let mut ws = WorldState::with_dimensions(5, 4);
+        ws.spawn_cell(1, 0, CellState::Alive).unwrap();
+        ws.spawn_cell(2, 0, CellState::Alive).unwrap();
+        ws.spawn_cell(3, 0, CellState::Dying(0)).unwrap();
+        ws.spawn_cell(0, 2, CellState::Alive).unwrap();
+        assert_eq!(write(&ws), "x = 5, y = 4, rule = B2/S/C3\n.2AB2$A!\n");
+    }
+
+    #[test]
+    fn test_parse_life_pattern_from_the_wiki() {
+        let rle = "#N Glider\n#C A comment\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!\n";
+        let ws = parse(rle).unwrap();
+        assert_eq!(ws.to_string(), ".O.\n..O\nOOO\n");
+        assert_eq!(ws.rule(), &"B3/S23".parse().unwrap());
+        assert_eq!(write(&ws), "x = 3, y = 3, rule = B3/S23/C2\nbo$2bo$3o!\n");
+    }
+
+    #[test]
+    fn test_parse_without_rule_uses_brian_s_brain() {
+        let ws = parse("x = 2, y = 1\nAB!").unwrap();
+        assert_eq!(ws.rule(), &Rule::default());
+        assert_eq!(ws.to_string(), "OX\n");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("#C empty"), Err(RleError::Header(String::new())));
+        assert_eq!(
+            parse("x = 3\nA!"),
+            Err(RleError::Header("x = 3".to_string()))
+        );
+        assert_eq!(
+            parse("x = 3, y = 1, rule = B9x/S/C3\nA!"),
+            Err(RleError::Rule(ParseRuleError::Count("x".to_string())))
+        );
+        assert_eq!(parse("x = 3, y = 1\nAzB!"), Err(RleError::Tag('z')));
+        assert_eq!(
+            parse("x = 3, y = 1\nAC!"),
+            Err(RleError::State("C".to_string()))
+        );
+        assert_eq!(
+            parse("x = 3, y = 1\n4A!"),
+            Err(RleError::Overflow { x: 3, y: 0 })
+        );
+        assert_eq!(
+            parse("x = 3, y = 1\nA$A!"),
+            Err(RleError::Overflow { x: 0, y: 1 })
+        );
+        assert_eq!(parse("x = 3, y = 1\nA"), Err(RleError::Unterminated));
+    }
+
+    #[test]
+    fn test_parse_dimension_errors() {
+        assert_eq!(parse("x = 0, y = 0\n!"), Err(RleError::Empty));
+        assert_eq!(parse("x = 3, y = 0\n!"), Err(RleError::Empty));
+        let huge = usize::MAX / 2;
+        assert_eq!(
+            parse(&format!("x = {huge}, y = 3\n!")),
+            Err(RleError::TooLarge {
+                width: huge,
+                height: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_huge_counts() {
+        let digits = "9".repeat(40);
+        assert_eq!(
+            parse(&format!("x = 3, y = 1\n{digits}A!")),
+            Err(RleError::Count)
+        );
+        let max = usize::MAX;
+        assert_eq!(
+            parse(&format!("x = 3, y = 1\nA{max}A!")),
+            Err(RleError::Overflow { x: 3, y: 0 })
+        );
+        assert_eq!(
+            parse(&format!("x = 3, y = 2\n{max}$A!")),
+            Err(RleError::Overflow { x: 0, y: max })
+        );
+    }
+}
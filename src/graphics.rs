@@ -1,6 +1,6 @@
 use crate::graphics::vulkan::*;
 use crate::graphics::window::*;
-use crate::WorldState;
+use crate::world_state::Automaton;
 
 use std::error::Error;
 use std::time::Duration;
@@ -18,10 +18,21 @@ use winit::event_loop::{ControlFlow, EventLoop};
 
 use winit_input_helper::WinitInputHelper;
 
+/// Module containing the creation of the vulkan objects
+/// and the vertices drawn on the screen.
 pub mod vulkan;
 mod window;
 
-pub fn run_gui(mut ws: WorldState, framerate: u64) -> Result<(), Box<dyn Error>> {
+/// Open a window drawing `ws`, which advances by one generation with up to
+/// `threads` threads every `framerate` milliseconds, until the window is
+/// closed or every cell is dead.
+///
+/// An error is returned if the vulkan context can't be created.
+pub fn run_gui<A: Automaton + 'static>(
+    mut ws: A,
+    framerate: u64,
+    threads: usize,
+) -> Result<(), Box<dyn Error>> {
     let library = VulkanLibrary::new()?;
     let required_extensions = vulkano_win::required_extensions(&library);
 
@@ -162,8 +173,8 @@ pub fn run_gui(mut ws: WorldState, framerate: u64) -> Result<(), Box<dyn Error>>
                 match future {
                     Ok(future) => {
                         previous_frame_end = Some(future.boxed());
-                        ws.next();
-                        if ws.as_vertices().is_empty() {
+                        ws.step(threads);
+                        if ws.is_extinct() {
                             *control_flow = ControlFlow::Exit;
                             return;
                         }
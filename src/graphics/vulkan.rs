@@ -31,13 +31,12 @@ use vulkano::VulkanLibrary;
 
 use winit::window::Window;
 
-// use repr(C) to prevent rust to mess with the data.
-#[repr(C)]
-#[derive(Clone, Copy, Debug, Default, PartialEq, Zeroable, Pod)]
-pub struct Vertex {
-    pub position: [f32; 2],
-    pub color: [f32; 3],
-}
+pub use crate::vertex::Vertex;
+
+// SAFETY: `Vertex` is `repr(C)` and only made of `f32`, without padding.
+unsafe impl Zeroable for Vertex {}
+unsafe impl Pod for Vertex {}
+
 impl_vertex!(Vertex, position, color);
 /// vulkan library and required extensions for the application.
 /// An error can be returned if the creation failed for any reason.
@@ -183,8 +182,13 @@ pub fn create_swapchain_and_images(
 
 pub fn create_vertex_buffer(
     device: &Arc<Device>,
-    vertices: Vec<Vertex>,
+    mut vertices: Vec<Vertex>,
 ) -> Result<Arc<CpuAccessibleBuffer<[Vertex]>>, AllocationCreationError> {
+    // a buffer can't be empty: without any cell to draw, it holds a
+    // triangle without area, which draws nothing.
+    if vertices.is_empty() {
+        vertices = vec![Vertex::default(); 3];
+    }
     CpuAccessibleBuffer::from_iter(
         &StandardMemoryAllocator::new_default(device.clone()),
         BufferUsage {
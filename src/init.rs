@@ -0,0 +1,705 @@
+use crate::world_state::{CellState, ConfigError, ParseWorldError, WorldState};
+
+use rand::prelude::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// A way of filling a world before running it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Init {
+    /// Random cells, chosen as described by the **Soup**.
+    Soup(Soup),
+
+    /// Copies of a pattern put side by side over the whole world,
+    /// the first one being at the top left corner of the world.
+    Tile(WorldState),
+}
+
+/// A random soup of **Alive** and **Dying** cells in an area of the world.
+///
+/// The cells of the area which are neither **Alive** nor **Dying** are
+/// **Dead**, and the cells outside of the area are left untouched.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Soup {
+    /// The proportion of **Alive** cells.
+    pub alive: f64,
+
+    /// The proportion of cells in the first stage of **Dying**.
+    pub dying: f64,
+
+    /// The part of the world filled by the soup.
+    pub area: Area,
+
+    /// How the proportions are turned into cells.
+    pub mode: Mode,
+
+    /// The symmetry of the soup, around the center of its area.
+    pub symmetry: Symmetry,
+}
+
+/// The part of a world filled by a **Soup**.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Area {
+    /// The whole world.
+    All,
+
+    /// The rectangle of `width` x `height` cells whose top left corner
+    /// is at column `x` and row `y`.
+    Rect {
+        /// The column of the top left corner.
+        x: i64,
+        /// The row of the top left corner.
+        y: i64,
+        /// The number of columns.
+        width: i64,
+        /// The number of rows.
+        height: i64,
+    },
+
+    /// The cells at most `radius` cells away from the cell at column `x`
+    /// and row `y`.
+    Disc {
+        /// The column of the center.
+        x: i64,
+        /// The row of the center.
+        y: i64,
+        /// The largest distance to the center.
+        radius: i64,
+    },
+}
+
+/// How the proportions of a **Soup** are turned into cells.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// Each cell is chosen on its own, with the proportions as
+    /// probabilities.
+    #[default]
+    Bernoulli,
+
+    /// The number of cells of each state is exactly the proportion of
+    /// the cells of the area.
+    Exact,
+
+    /// As **Bernoulli**, with probabilities growing from 0 on the left
+    /// of the area to the proportions on its right.
+    Gradient,
+}
+
+/// The symmetries of a **Soup**, named as in apgsearch.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Symmetry {
+    /// No symmetry.
+    #[default]
+    C1,
+
+    /// The soup is the same once turned by half a turn.
+    C2,
+
+    /// The soup is the same once turned by a quarter of a turn.
+    C4,
+
+    /// The soup is the same once turned by a quarter of a turn, or
+    /// mirrored along its axes and diagonals.
+    D8,
+}
+
+/// A transformation of the coordinates of a cell.
+///
+/// The coordinates are wider than the ones of the areas, so that the areas
+/// far outside of the world can be transformed without overflowing.
+type Transformation = fn(i128, i128) -> (i128, i128);
+
+/// The reasons why an initialization can't be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseInitError {
+    /// The initialization isn't one of the known kinds.
+    Kind(String),
+
+    /// A parameter of a soup is unknown or has an invalid value.
+    Parameter(String),
+
+    /// The proportions of a soup aren't between 0 and 1, or add up to
+    /// more than 1.
+    Density,
+
+    /// The pattern to tile can't be parsed, or is empty.
+    Pattern(Option<ParseWorldError>),
+}
+
+impl fmt::Display for ParseInitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseInitError::Kind(kind) => write!(f, "`{kind}` isn't one of `soup` or `tile`"),
+            ParseInitError::Parameter(parameter) => {
+                write!(f, "`{parameter}` isn't a valid parameter of a soup")
+            }
+            ParseInitError::Density => write!(
+                f,
+                "the proportions must be between 0 and 1, and add up to at most 1"
+            ),
+            ParseInitError::Pattern(Some(e)) => write!(f, "the pattern is invalid: {e}"),
+            ParseInitError::Pattern(None) => write!(f, "the pattern is empty"),
+        }
+    }
+}
+
+impl Error for ParseInitError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseInitError::Pattern(Some(e)) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Soup {
+    /// Return a soup of half **Alive** cells over the whole world.
+    fn default() -> Soup {
+        Soup {
+            alive: 0.5,
+            dying: 0.0,
+            area: Area::All,
+            mode: Mode::Bernoulli,
+            symmetry: Symmetry::C1,
+        }
+    }
+}
+
+impl fmt::Display for Init {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Init::Soup(soup) => {
+                let default = Soup::default();
+                let mut parameters = vec![];
+                if soup.alive != default.alive {
+                    parameters.push(format!("alive={}", soup.alive));
+                }
+                if soup.dying != default.dying {
+                    parameters.push(format!("dying={}", soup.dying));
+                }
+                match soup.area {
+                    Area::All => {}
+                    Area::Rect {
+                        x,
+                        y,
+                        width,
+                        height,
+                    } => parameters.push(format!("rect={x},{y},{width},{height}")),
+                    Area::Disc { x, y, radius } => {
+                        parameters.push(format!("disc={x},{y},{radius}"))
+                    }
+                }
+                match soup.mode {
+                    Mode::Bernoulli => {}
+                    Mode::Exact => parameters.push("mode=exact".to_string()),
+                    Mode::Gradient => parameters.push("mode=gradient".to_string()),
+                }
+                match soup.symmetry {
+                    Symmetry::C1 => {}
+                    Symmetry::C2 => parameters.push("symmetry=c2".to_string()),
+                    Symmetry::C4 => parameters.push("symmetry=c4".to_string()),
+                    Symmetry::D8 => parameters.push("symmetry=d8".to_string()),
+                }
+                if parameters.is_empty() {
+                    write!(f, "soup")
+                } else {
+                    write!(f, "soup:{}", parameters.join(";"))
+                }
+            }
+            Init::Tile(pattern) => {
+                let rows: Vec<String> = pattern.to_string().lines().map(String::from).collect();
+                write!(f, "tile:{}", rows.join("/"))
+            }
+        }
+    }
+}
+
+impl FromStr for Init {
+    type Err = ParseInitError;
+
+    /// Parse an initialization written `soup`, optionally followed by
+    /// parameters separated by semicolons (e.g. `soup:alive=0.3;dying=0.1`),
+    /// or `tile:` followed by the rows of a pattern separated by slashes,
+    /// written as `WorldState`'s `Display` does (e.g. `tile:.O./OXO`).
+    ///
+    /// The parameters of a soup are `alive=P` and `dying=P` for the
+    /// proportions, `rect=X,Y,W,H` or `disc=X,Y,R` for the area,
+    /// `mode=bernoulli|exact|gradient` and `symmetry=c1|c2|c4|d8`.
+    fn from_str(s: &str) -> Result<Init, ParseInitError> {
+        let (kind, parameters) = match s.trim().split_once(':') {
+            Some((kind, parameters)) => (kind, Some(parameters)),
+            None => (s.trim(), None),
+        };
+
+        match (kind.to_ascii_lowercase().as_str(), parameters) {
+            ("soup", None) => Ok(Init::Soup(Soup::default())),
+            ("soup", Some(parameters)) => {
+                let mut soup = Soup::default();
+                for parameter in parameters.split(';') {
+                    parse_parameter(&mut soup, parameter.trim())
+                        .ok_or_else(|| ParseInitError::Parameter(parameter.to_string()))?;
+                }
+                let density = 0.0..=1.0;
+                if density.contains(&soup.alive)
+                    && density.contains(&soup.dying)
+                    && density.contains(&(soup.alive + soup.dying))
+                {
+                    Ok(Init::Soup(soup))
+                } else {
+                    Err(ParseInitError::Density)
+                }
+            }
+            ("tile", Some(rows)) => {
+                let pattern: WorldState = rows
+                    .replace('/', "\n")
+                    .parse()
+                    .map_err(|e| ParseInitError::Pattern(Some(e)))?;
+                if pattern.width() > 0 && pattern.height() > 0 {
+                    Ok(Init::Tile(pattern))
+                } else {
+                    Err(ParseInitError::Pattern(None))
+                }
+            }
+            _ => Err(ParseInitError::Kind(s.to_string())),
+        }
+    }
+}
+
+/// Change the field of `soup` given by `parameter`, written `key=value`.
+/// `None` is returned if the parameter is invalid.
+fn parse_parameter(soup: &mut Soup, parameter: &str) -> Option<()> {
+    let (key, value) = parameter.split_once('=')?;
+    let numbers: Vec<i64> = value
+        .split(',')
+        .map(|n| n.trim().parse())
+        .collect::<Result<_, _>>()
+        .unwrap_or_default();
+    match (key.trim(), value.trim().to_ascii_lowercase().as_str()) {
+        ("alive", value) => soup.alive = value.parse().ok()?,
+        ("dying", value) => soup.dying = value.parse().ok()?,
+        ("rect", _) => match numbers[..] {
+            [x, y, width, height] if width > 0 && height > 0 => {
+                soup.area = Area::Rect {
+                    x,
+                    y,
+                    width,
+                    height,
+                }
+            }
+            _ => return None,
+        },
+        ("disc", _) => match numbers[..] {
+            [x, y, radius] if radius >= 0 && radius.checked_pow(2).is_some() => {
+                soup.area = Area::Disc { x, y, radius }
+            }
+            _ => return None,
+        },
+        ("mode", "bernoulli") => soup.mode = Mode::Bernoulli,
+        ("mode", "exact") => soup.mode = Mode::Exact,
+        ("mode", "gradient") => soup.mode = Mode::Gradient,
+        ("symmetry", "c1") => soup.symmetry = Symmetry::C1,
+        ("symmetry", "c2") => soup.symmetry = Symmetry::C2,
+        ("symmetry", "c4") => soup.symmetry = Symmetry::C4,
+        ("symmetry", "d8") => soup.symmetry = Symmetry::D8,
+        _ => return None,
+    }
+    Some(())
+}
+
+impl Init {
+    /// Fill `ws` with this initialization, the random choices being made
+    /// from `seed`: the same seed always gives the same world.
+    ///
+    /// An error is returned, and the world left untouched, if a soup with
+    /// the C4 or the D8 symmetry has an area which isn't a square.
+    pub fn apply(&self, ws: &mut WorldState, seed: u64) -> Result<(), ConfigError> {
+        match self {
+            Init::Soup(soup) => soup.apply(ws, seed)?,
+            Init::Tile(pattern) => {
+                for y in 0..ws.height() {
+                    for x in 0..ws.width() {
+                        let cell = pattern.get(x % pattern.width(), y % pattern.height());
+                        ws.set(x, y, cell.unwrap()).unwrap();
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Area {
+    /// Return the rectangle (column, row, width, height) around the area,
+    /// in a world of `width` x `height` cells.
+    fn bounds(self, width: usize, height: usize) -> (i128, i128, i128, i128) {
+        match self {
+            Area::All => (0, 0, width as i128, height as i128),
+            Area::Rect {
+                x,
+                y,
+                width,
+                height,
+            } => (x.into(), y.into(), width.into(), height.into()),
+            Area::Disc { x, y, radius } => {
+                let (x, y, radius) = (i128::from(x), i128::from(y), i128::from(radius));
+                (x - radius, y - radius, 2 * radius + 1, 2 * radius + 1)
+            }
+        }
+    }
+
+    /// Return whether the cell at column `x` and row `y`, inside of the
+    /// bounds of the area, is in the area.
+    fn contains(self, x: i128, y: i128) -> bool {
+        match self {
+            Area::All | Area::Rect { .. } => true,
+            Area::Disc {
+                x: cx,
+                y: cy,
+                radius,
+            } => {
+                (x - i128::from(cx)).pow(2) + (y - i128::from(cy)).pow(2)
+                    <= i128::from(radius).pow(2)
+            }
+        }
+    }
+}
+
+impl Symmetry {
+    /// Return the transformations of the soup, as functions of the
+    /// coordinates of a cell relative to the center of the area, doubled
+    /// so that they stay whole numbers.
+    fn transformations(self) -> &'static [Transformation] {
+        match self {
+            Symmetry::C1 => &[|a, b| (a, b)],
+            Symmetry::C2 => &[|a, b| (a, b), |a, b| (-a, -b)],
+            Symmetry::C4 => &[
+                |a, b| (a, b),
+                |a, b| (-a, -b),
+                |a, b| (-b, a),
+                |a, b| (b, -a),
+            ],
+            Symmetry::D8 => &[
+                |a, b| (a, b),
+                |a, b| (-a, -b),
+                |a, b| (-b, a),
+                |a, b| (b, -a),
+                |a, b| (-a, b),
+                |a, b| (a, -b),
+                |a, b| (b, a),
+                |a, b| (-b, -a),
+            ],
+        }
+    }
+}
+
+impl Soup {
+    /// Fill the area of the soup in `ws`, the random choices being made
+    /// from `seed`.
+    ///
+    /// The cells which are the same by symmetry are given the state of
+    /// the first of them, row by row: the proportions of **Mode::Exact**
+    /// are the ones of these first cells.
+    ///
+    /// An error is returned if the soup is turned by a quarter of a turn
+    /// by its symmetry, and its area isn't a square.
+    fn apply(&self, ws: &mut WorldState, seed: u64) -> Result<(), ConfigError> {
+        let (x0, y0, width, height) = self.area.bounds(ws.width(), ws.height());
+        if matches!(self.symmetry, Symmetry::C4 | Symmetry::D8) && width != height {
+            return Err(ConfigError::Symmetry {
+                width: width as u64,
+                height: height as u64,
+            });
+        }
+
+        // the columns and rows of the area inside of the world, however
+        // large the area is.
+        let columns = (-x0).max(0)..width.min(ws.width() as i128 - x0);
+        let rows = (-y0).max(0)..height.min(ws.height() as i128 - y0);
+        if columns.is_empty() || rows.is_empty() {
+            return Ok(());
+        }
+        let (left, top) = ((x0 + columns.start) as usize, (y0 + rows.start) as usize);
+        let rect_width = (columns.end - columns.start) as usize;
+        let rect_height = (rows.end - rows.start) as usize;
+        let area = |i: usize, j: usize| (columns.start + i as i128, rows.start + j as i128);
+        let in_soup = |u: i128, v: i128| {
+            columns.contains(&u) && rows.contains(&v) && self.area.contains(x0 + u, y0 + v)
+        };
+
+        // the index, in the rectangle of the area inside of the world, of
+        // the first cell which is the same as the cell (`u`, `v`) by
+        // symmetry.
+        let first = |u: i128, v: i128| {
+            let (a, b) = (2 * u - (width - 1), 2 * v - (height - 1));
+            let (u, v) = self
+                .symmetry
+                .transformations()
+                .iter()
+                .map(|transform| transform(a, b))
+                .map(|(a, b)| (a + width - 1, b + height - 1))
+                .filter(|&(a, b)| a % 2 == 0 && b % 2 == 0)
+                .map(|(a, b)| (a / 2, b / 2))
+                .filter(|&(u, v)| in_soup(u, v))
+                .min_by_key(|&(u, v)| (v, u))
+                .unwrap_or((u, v));
+            (v - rows.start) as usize * rect_width + (u - columns.start) as usize
+        };
+
+        // the first cell of each cell of the soup, `NOT_IN_SOUP` marking
+        // the cells of the rectangle outside of the soup, and the cells
+        // which are their own first one, row by row.
+        const NOT_IN_SOUP: u32 = u32::MAX;
+        let mut first_of = vec![NOT_IN_SOUP; rect_width * rect_height];
+        let mut firsts: Vec<u32> = vec![];
+        for j in 0..rect_height {
+            for i in 0..rect_width {
+                let (u, v) = area(i, j);
+                if in_soup(u, v) {
+                    let k = j * rect_width + i;
+                    first_of[k] = first(u, v) as u32;
+                    if first_of[k] as usize == k {
+                        firsts.push(k as u32);
+                    }
+                }
+            }
+        }
+
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut states = vec![CellState::Dead; rect_width * rect_height];
+        match self.mode {
+            Mode::Bernoulli | Mode::Gradient => {
+                for &k in &firsts {
+                    let rate = match self.mode {
+                        Mode::Gradient => {
+                            let (u, _) = area(k as usize % rect_width, 0);
+                            (u as f64 + 0.5) / width as f64
+                        }
+                        _ => 1.0,
+                    };
+                    let p: f64 = rng.gen();
+                    states[k as usize] = if p < self.alive * rate {
+                        CellState::Alive
+                    } else if p < (self.alive + self.dying) * rate {
+                        CellState::Dying(0)
+                    } else {
+                        CellState::Dead
+                    };
+                }
+            }
+            Mode::Exact => {
+                let alive = (self.alive * firsts.len() as f64) as usize;
+                let dying = (self.dying * firsts.len() as f64) as usize;
+                firsts.shuffle(&mut rng);
+                for (i, &k) in firsts.iter().enumerate() {
+                    states[k as usize] = match i {
+                        i if i < alive => CellState::Alive,
+                        i if i < alive + dying => CellState::Dying(0),
+                        _ => CellState::Dead,
+                    };
+                }
+            }
+        }
+
+        for (k, &first) in first_of.iter().enumerate() {
+            if first != NOT_IN_SOUP {
+                let (x, y) = (left + k % rect_width, top + k / rect_width);
+                ws.set(x, y, states[first as usize]).unwrap();
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse_display_round_trip() {
+        for init in [
+            "soup",
+            "soup:alive=0.3;dying=0.1",
+            "soup:rect=2,3,10,5;mode=exact",
+            "soup:disc=8,8,4;mode=gradient;symmetry=d8",
+            "soup:alive=0.2;symmetry=c4",
+            "tile:.O./OXO",
+        ] {
+            assert_eq!(init.parse::<Init>().unwrap().to_string(), init);
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "noise".parse::<Init>(),
+            Err(ParseInitError::Kind("noise".to_string()))
+        );
+        assert_eq!(
+            "soup:symmetry=c3".parse::<Init>(),
+            Err(ParseInitError::Parameter("symmetry=c3".to_string()))
+        );
+        assert_eq!(
+            "soup:rect=1,2,3".parse::<Init>(),
+            Err(ParseInitError::Parameter("rect=1,2,3".to_string()))
+        );
+        assert_eq!(
+            "soup:alive=0.7;dying=0.4".parse::<Init>(),
+            Err(ParseInitError::Density)
+        );
+        assert_eq!(
+            "tile:O?".parse::<Init>(),
+            Err(ParseInitError::Pattern(Some(ParseWorldError::Cell('?'))))
+        );
+        assert_eq!("tile:".parse::<Init>(), Err(ParseInitError::Pattern(None)));
+    }
+
+    #[test]
+    fn test_exact_soup_in_a_rectangle() {
+        let mut ws = WorldState::new(20);
+        let init: Init = "soup:alive=0.25;dying=0.5;rect=5,5,10,4;mode=exact"
+            .parse()
+            .unwrap();
+        init.apply(&mut ws, 1).unwrap();
+        assert_eq!(ws.population(CellState::Alive), 10);
+        assert_eq!(ws.population(CellState::Dying(0)), 20);
+        for (x, y, state) in ws.iter() {
+            if !(5..15).contains(&x) || !(5..9).contains(&y) {
+                assert_eq!(state, CellState::Dead);
+            }
+        }
+    }
+
+    #[test]
+    fn test_soup_in_a_disc() {
+        let mut ws = WorldState::new(11);
+        let init: Init = "soup:alive=1;disc=5,5,3".parse().unwrap();
+        init.apply(&mut ws, 1).unwrap();
+        assert_eq!(ws.population(CellState::Alive), 29);
+        assert_eq!(ws.get(5, 2), Ok(CellState::Alive));
+        assert_eq!(ws.get(2, 2), Ok(CellState::Dead));
+    }
+
+    #[test]
+    fn test_huge_areas() {
+        // only the cells inside of the world are gone through.
+        let mut ws = WorldState::new(8);
+        let rect = format!(
+            "soup:alive=1;rect=-{0},-{0},{1},{1}",
+            i64::MAX / 2,
+            i64::MAX
+        );
+        rect.parse::<Init>().unwrap().apply(&mut ws, 1).unwrap();
+        assert_eq!(ws.population(CellState::Alive), 64);
+        let mut ws = WorldState::new(8);
+        let disc: Init = "soup:alive=1;disc=4,4,3037000499;symmetry=d8"
+            .parse()
+            .unwrap();
+        disc.apply(&mut ws, 1).unwrap();
+        assert_eq!(ws.population(CellState::Alive), 64);
+        assert_eq!(
+            "soup:disc=0,0,3037000500".parse::<Init>(),
+            Err(ParseInitError::Parameter("disc=0,0,3037000500".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_same_seed_same_soup() {
+        let init: Init = "soup:alive=0.3;dying=0.2".parse().unwrap();
+        let (mut ws1, mut ws2) = (WorldState::new(16), WorldState::new(16));
+        init.apply(&mut ws1, 3).unwrap();
+        init.apply(&mut ws2, 3).unwrap();
+        assert_eq!(ws1, ws2);
+        init.apply(&mut ws2, 4).unwrap();
+        assert_ne!(ws1, ws2);
+    }
+
+    #[test]
+    fn test_symmetric_soups() {
+        for (symmetry, transforms) in [
+            ("c2", vec![(true, true, false)]),
+            ("c4", vec![(true, true, false), (true, false, true)]),
+            (
+                "d8",
+                vec![
+                    (true, false, false),
+                    (false, false, true),
+                    (true, true, false),
+                ],
+            ),
+        ] {
+            let mut ws = WorldState::new(12);
+            let init: Init = format!("soup:symmetry={symmetry}").parse().unwrap();
+            init.apply(&mut ws, 9).unwrap();
+            for (x, y, state) in ws.iter() {
+                for &(flip_x, flip_y, swap) in &transforms {
+                    let (mut tx, mut ty) = (x, y);
+                    if swap {
+                        (tx, ty) = (ty, tx);
+                    }
+                    if flip_x {
+                        tx = 11 - tx;
+                    }
+                    if flip_y {
+                        ty = 11 - ty;
+                    }
+                    assert_eq!(ws.get(tx, ty), Ok(state), "{symmetry} at ({x}, {y})");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_quarter_turn_symmetries_need_a_square() {
+        let mut ws = WorldState::with_dimensions(12, 9);
+        for init in ["soup:symmetry=c4", "soup:rect=1,1,5,4;symmetry=d8"] {
+            let init: Init = init.parse().unwrap();
+            assert!(matches!(
+                init.apply(&mut ws, 1),
+                Err(ConfigError::Symmetry { .. })
+            ));
+        }
+        assert_eq!(ws.population(CellState::Dead), 12 * 9);
+
+        // a disc and a square inside of a rectangular world are fine.
+        for init in [
+            "soup:disc=6,4,3;symmetry=c4",
+            "soup:rect=1,1,7,7;symmetry=d8",
+        ] {
+            let init: Init = init.parse().unwrap();
+            assert_eq!(init.apply(&mut ws, 1), Ok(()));
+        }
+        assert_eq!(
+            "soup:symmetry=c2"
+                .parse::<Init>()
+                .unwrap()
+                .apply(&mut ws, 1),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_gradient_soup_is_denser_on_the_right() {
+        let mut ws = WorldState::new(40);
+        let init: Init = "soup:alive=1;mode=gradient".parse().unwrap();
+        init.apply(&mut ws, 5).unwrap();
+        let alive = |columns: std::ops::Range<usize>| {
+            ws.iter()
+                .filter(|&(x, _, state)| columns.contains(&x) && state == CellState::Alive)
+                .count()
+        };
+        assert!(alive(0..10) < alive(30..40));
+    }
+
+    #[test]
+    fn test_tile() {
+        let mut ws = WorldState::with_dimensions(5, 3);
+        let init: Init = "tile:OX/..".parse().unwrap();
+        init.apply(&mut ws, 0).unwrap();
+        assert_eq!(ws.to_string(), "OXOXO\n.....\nOXOXO\n");
+    }
+}
@@ -0,0 +1,64 @@
+//! Library running the Brian's Brain cellular automaton, and the other
+//! rules of the *Generations* family.
+//!
+//! A world is a [`WorldState`](world_state::WorldState), whose cells can be
+//! read and changed with `get`, `set` and `iter`, and in which the known
+//! patterns can be placed with the [`Spawn`](world_state::Spawn) trait.
+//! It advances one generation at a time with `next`.
+
+/// Module containing the census of the objects
+/// left in a world, separated and classified.
+pub mod census;
+
+/// Module containing the file formats in which
+/// the worlds can be loaded and saved.
+pub mod format;
+
+/// Module containing vulkan initialization and
+/// window handling, built with the `gui` feature.
+#[cfg(feature = "gui")]
+pub mod graphics;
+
+/// Module containing the ways a world can be
+/// filled before running it.
+pub mod init;
+
+/// Module containing the cells which can be counted
+/// as the neighbours of a cell.
+pub mod neighbourhood;
+
+/// Module containing the patterns which can be
+/// stamped in a world, and their catalogue.
+pub mod pattern;
+
+/// Module containing the rules of the Generations
+/// family, which the cells follow.
+pub mod rule;
+
+/// Module containing the scenarios, describing
+/// a world to run in a file.
+pub mod scenario;
+
+/// Module containing the search for the objects
+/// left by random soups which aren't catalogued.
+pub mod search;
+
+/// Module containing the statistics measured on
+/// each generation of a world.
+pub mod stats;
+
+/// Module containing the sweeps, running many random
+/// worlds to aggregate how they end.
+pub mod sweep;
+
+/// Module containing the ways the edges of the
+/// cellular automaton's world can be connected.
+pub mod topology;
+
+/// Module containing the vertices in which the
+/// cells are drawn, without depending on vulkan.
+pub mod vertex;
+
+/// Module containing the cellular automaton
+/// (cells, environment, rules, etc.).
+pub mod world_state;
@@ -1,29 +1,50 @@
-use crate::graphics::run_gui;
-use crate::world_state::WorldState;
+use brian_s_brain::census::census;
+use brian_s_brain::format::Format;
+#[cfg(feature = "gui")]
+use brian_s_brain::graphics::run_gui;
+use brian_s_brain::init::{Init, Mode, Soup};
+use brian_s_brain::neighbourhood::Neighbourhood;
+use brian_s_brain::pattern::Placement;
+use brian_s_brain::rule::Rule;
+use brian_s_brain::scenario::Scenario;
+use brian_s_brain::search::Search;
+use brian_s_brain::stats::{Stats, StatsFormat};
+use brian_s_brain::sweep::{Report, Span, Sweep};
+use brian_s_brain::topology::Topology;
+use brian_s_brain::world_state::bit_world::BitWorld;
+use brian_s_brain::world_state::damage::{Damaged, Spread};
+use brian_s_brain::world_state::hash_life::HashLife;
+use brian_s_brain::world_state::sparse_world::SparseWorld;
+use brian_s_brain::world_state::{Automaton, ConfigError, Spawn, WorldState};
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 use std::thread;
 use std::time::Duration;
 use std::time::Instant;
 
-/// Module containing vulkan initialization and
-/// window handling.
-mod graphics;
-
-/// Module containing the cellular automaton
-/// (cells, environment, rules, etc.).
-mod world_state;
-
 /// Program to run the Brian's Brain cellular automaton.
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Do 100 runs of the program and for each of them:
     ///
-    /// 1. Declare the size of the cellular automaton to be 100x100 (`WorldState::new()`)
+    /// 1. Declare the size of the cellular automaton to be 100x100,
+    ///    or the one given with `--size` (`WorldState::new()`)
     /// 2. Initialize the world with 50% random noise (`WorldState::randomize()`)
     /// 3. Do 100 iterations (`WorldState::next()`)
+    /// 4. Do 100 iterations on a bit-packed copy of the world (`BitWorld::next()`)
+    ///
+    /// With `--threads`, the iterations are also timed when run in parallel
+    /// (`WorldState::next_parallel()` and `BitWorld::next_parallel()`).
     ///
     /// Then, the average execution time for each call is displayed.
     #[arg(short, long, action, verbatim_doc_comment, default_value_t = false)]
@@ -34,7 +55,21 @@ struct Args {
     #[arg(long, action, default_value_t = false)]
     cli: bool,
 
-    /// Run the program with a specific start.
+    /// Run the world, without displaying it, until it settles as with
+    /// `--until-stable`, then print the census of the objects left: their
+    /// number, name in the catalogue, period, velocity and code. Two cells
+    /// belong to the same object when they are at most the given number
+    /// of columns and rows apart [default: 2].
+    #[arg(long, num_args = 0..=1, default_missing_value = "2", conflicts_with = "unbounded")]
+    census: Option<usize>,
+
+    /// With `--cli`, highlight the cells which changed since the previous
+    /// generation, and print how many they are.
+    #[arg(long, action, default_value_t = false, requires = "cli", conflicts_with_all = ["gui", "unbounded"])]
+    diff: bool,
+
+    /// Run the program with a specific start, one of the scenarios bundled
+    /// from the `scenarios` directory.
     ///
     /// - `--example=1` depicts 5 period-3 oscillators.
     /// - `--example=2` depicts gliders creating a breeder.
@@ -52,97 +87,687 @@ struct Args {
     #[arg(short, long, action, default_value_t = false)]
     gui: bool,
 
+    /// Fill the world with a generator, instead of `--randomness`:
+    ///
+    /// - `soup` for random cells, optionally followed by parameters
+    ///   separated by semicolons (e.g. `soup:alive=0.3;dying=0.1`):
+    ///   - `alive=P` and `dying=P`, the proportions of the states.
+    ///   - `rect=X,Y,W,H` or `disc=X,Y,R`, the area of the soup.
+    ///   - `mode=bernoulli`, `mode=exact` or `mode=gradient`.
+    ///   - `symmetry=c1`, `c2`, `c4` or `d8`, the last two in a square area.
+    /// - `tile:ROW/ROW/...` to repeat a pattern over the whole world
+    ///   (e.g. `tile:.O./OXO`).
+    ///
+    /// The random choices are made from `--seed`.
+    #[arg(long, verbatim_doc_comment)]
+    init: Option<Init>,
+
+    /// Jump over this number of generations before displaying the world.
+    /// The jump is computed with the HashLife algorithm, which can handle
+    /// billions of generations, as if the world had no edges. Worlds whose
+    /// topology connects their edges can only jump with `--unbounded`.
+    #[arg(short, long)]
+    jump: Option<u64>,
+
+    /// Load the world from a file, instead of using `--example` or a random
+    /// world. The format depends on the extension: `.cells` for plaintext,
+    /// `.mc` for Macrocell, and RLE otherwise.
+    #[arg(long)]
+    load: Option<PathBuf>,
+
+    /// The cells counted as the neighbours of a cell:
+    ///
+    /// - `moore` for the 8 surrounding cells, `moore:R` for the cells
+    ///   in the square of radius `R`.
+    /// - `von-neumann` for the 4 orthogonal cells, `von-neumann:R` for
+    ///   the cells at a Manhattan distance up to `R`.
+    /// - `hexagonal` for the 6 surrounding cells of a hexagonal grid.
+    /// - `custom:X,Y;X,Y;...` for the cells at the given offsets.
+    ///
+    /// The radius and the offsets go up to 50 cells.
+    ///
+    /// The numbers of alive neighbours of `--rule` are counted in it.
+    /// [default: the neighbourhood of the scenario, or moore]
+    #[arg(short, long, verbatim_doc_comment)]
+    neighbourhood: Option<Neighbourhood>,
+
+    /// Stamp a pattern of the catalogue on the world, written
+    /// `name@x,y` with its top left corner at column `x` and row `y`,
+    /// optionally turned clockwise (e.g. `glider@10,4,90`).
+    /// It can be given several times, and is applied on top of the world.
+    /// The catalogue holds oscillators, spaceships, a puffer (`wick`) and
+    /// a rake (`rake8`) of Brian's Brain.
+    #[arg(long)]
+    pattern: Vec<Placement>,
+
+    /// The number of threads used to compute each iteration.
+    #[arg(long, default_value_t = 1)]
+    threads: usize,
+
     /// The number of iterations to run for.
     #[arg(short, long, default_value_t = 100)]
     iter: u16,
 
     /// The percentage of cell alive at the beginning.
-    /// The cells are chosen randomly.  
-    #[arg(short, long, default_value_t = 0.5)]
-    randomness: f64,
+    /// The cells are chosen randomly [default: 0.5].
+    #[arg(short, long)]
+    randomness: Option<f64>,
+
+    /// The rule followed by the cells, written `B.../S.../C...`:
+    /// the numbers of alive neighbours for a dead cell to be born,
+    /// the ones for an alive cell to survive, and the number of states.
+    /// `--jump` and `--benchmark` only support the rule of Brian's Brain.
+    /// Beyond 9 neighbours, numbers are separated by commas (e.g. `B3,10-12`).
+    /// [default: the rule of the file given with `--load` or of the
+    /// scenario, or B2/S/C3]
+    #[arg(long)]
+    rule: Option<Rule>,
 
-    /// The size of the world in which the cells live.    
-    #[arg(short, long, default_value_t = 50)]
-    size: u16,
+    /// Run the world described by a JSON scenario file, instead of using
+    /// `--example` or a random world: its dimensions, topology, rule,
+    /// neighbourhood, generator (`init`), seed and patterns. The options
+    /// given on the command line take precedence over the ones of the file.
+    #[arg(long, conflicts_with_all = ["load", "example"])]
+    scenario: Option<PathBuf>,
+
+    /// The seed from which the cells of a random world are chosen, so that
+    /// the same world can be run again [default: the seed of the scenario,
+    /// or a random seed, printed].
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Save the world to a file before running it, after `--jump`.
+    /// The format depends on the extension, as for `--load`.
+    /// With `--unbounded`, only the displayed part of the world is saved.
+    #[arg(long)]
+    save: Option<PathBuf>,
+
+    /// Run the world for `--iter` generations without displaying it, and
+    /// write the statistics of each generation to the given file, or to
+    /// the standard output with `-`: the numbers of alive, dying and dead
+    /// cells, the births, the activity (the cells which changed), and the
+    /// bounding box and centroid of the cells which aren't dead.
+    #[arg(long, num_args = 0..=1, default_missing_value = "-", conflicts_with = "unbounded")]
+    stats: Option<PathBuf>,
+
+    /// The format of `--stats` [default: json-lines for a `.jsonl` or
+    /// `.json` file, or csv].
+    #[arg(long, value_enum)]
+    stats_format: Option<StatsFormat>,
+
+    /// The size of the world in which the cells live [default: 50].
+    #[arg(short, long)]
+    size: Option<usize>,
+
+    /// Run the world, without displaying it, until its cells repeat
+    /// themselves or die, and print the generation at which it happens
+    /// and the period of the cycle, with the displacement of the cells
+    /// for a spaceship. It gives up after the given number of generations
+    /// [default: 10000].
+    #[arg(long, num_args = 0..=1, default_missing_value = "10000", conflicts_with = "unbounded")]
+    until_stable: Option<u64>,
+
+    /// Run the world without edges, so that patterns can grow forever.
+    /// The initial world is used as the part of the world displayed.
+    #[arg(short, long, action, default_value_t = false)]
+    unbounded: bool,
+
+    /// How the edges of the world are connected to each other
+    /// [default: the topology of the scenario, or bounded].
+    #[arg(short, long, value_enum)]
+    topology: Option<Topology>,
+
+    /// The number of cells on each row of the world [default: `--size`].
+    #[arg(long)]
+    width: Option<usize>,
+
+    /// The number of rows of the world [default: `--size`].
+    #[arg(long)]
+    height: Option<usize>,
+}
+
+/// The tools run instead of displaying a world.
+#[derive(Subcommand)]
+enum Command {
+    /// Run many random worlds in parallel, and print how they ended.
+    ///
+    /// The worlds are square, with every combination of the given sizes,
+    /// proportions of alive cells and seeds. They are run without being
+    /// displayed until they die, repeat themselves or reach
+    /// `--max-generations`. Then, for each size and proportion, the mean and
+    /// median lifetime (the generation at which the worlds settled), the
+    /// mean final population and how the worlds ended are printed.
+    ///
+    /// The values are separated by commas, and ranges are written
+    /// `START..END:STEP`, the end being included and the step being 1 by
+    /// default for sizes and seeds (e.g. `--randomness=0.1..0.9:0.1`). Each
+    /// option takes up to 10000 values, and a sweep has up to 1000000 worlds.
+    Sweep {
+        /// The sizes of the worlds.
+        #[arg(short, long, default_value = "50")]
+        size: Span<usize>,
+
+        /// The proportions of alive cells at the beginning.
+        #[arg(short, long, default_value = "0.5")]
+        randomness: Span<f64>,
+
+        /// The seeds from which the cells are chosen.
+        #[arg(long, default_value = "0..9")]
+        seed: Span<u64>,
+
+        /// The number of generations after which a world is given up on.
+        #[arg(short, long, default_value_t = 10000)]
+        max_generations: u64,
+
+        /// The number of worlds run at the same time [default: the number
+        /// of processors].
+        #[arg(long)]
+        threads: Option<usize>,
+    },
+
+    /// Search random soups for the objects which aren't in the catalogue.
+    ///
+    /// As apgsearch does, square soups are filled at random, each one from
+    /// its own seed, run until they settle, and the objects left are
+    /// censused as with `--census`. The new oscillators, spaceships and
+    /// puffers are printed with the seed of their soup, and appended to
+    /// the results file. The search resumes after the soups already
+    /// searched when it's run again with the same results file.
+    Search {
+        /// The size of the soups.
+        #[arg(short, long, default_value_t = 50)]
+        size: usize,
+
+        /// The proportion of alive cells in the soups.
+        #[arg(short, long, default_value_t = 0.5)]
+        randomness: f64,
+
+        /// The number of generations after which a soup which hasn't
+        /// settled is censused anyway.
+        #[arg(short, long, default_value_t = 10000)]
+        max_generations: u64,
+
+        /// The distance below which two cells belong to the same object,
+        /// as for `--census`.
+        #[arg(short, long, default_value_t = 2)]
+        distance: usize,
+
+        /// The file to which the results are appended.
+        #[arg(long, default_value = "search.log")]
+        results: PathBuf,
+
+        /// The number of soups searched before stopping [default: the
+        /// search goes on until it's interrupted].
+        #[arg(long)]
+        soups: Option<u64>,
+
+        /// The number of soups run at the same time [default: the number
+        /// of processors].
+        #[arg(long)]
+        threads: Option<usize>,
+    },
+
+    /// Measure how a single flipped cell spreads through the world.
+    ///
+    /// The world given by the options before the subcommand is run for
+    /// `--iter` generations next to a copy of it in which a cell is flipped
+    /// (a dead cell made alive, or another cell made dead). The number of
+    /// cells which differ between them (the Hamming distance) and the
+    /// bounding box of these cells are written as CSV for each generation.
+    Damage {
+        /// The column of the flipped cell [default: the middle column].
+        #[arg(short)]
+        x: Option<usize>,
+
+        /// The row of the flipped cell [default: the middle row].
+        #[arg(short)]
+        y: Option<usize>,
+
+        /// The file to which the CSV is written, or `-` for the standard
+        /// output.
+        #[arg(short, long, default_value = "-")]
+        output: PathBuf,
+
+        /// Show the copy with the flipped cell in a window instead, the
+        /// cells which differ from the world being drawn in yellow.
+        #[arg(short, long, action, default_value_t = false)]
+        gui: bool,
+    },
 }
 
 /// Entry point of the program.
-fn main() {
-    let args = Args::parse();
+///
+/// The errors are reported on the standard error, the program exiting
+/// with a non-zero code.
+fn main() -> ExitCode {
+    match try_main(Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Run the program as asked by `args`.
+fn try_main(args: Args) -> Result<(), Box<dyn Error>> {
+    match &args.command {
+        Some(Command::Sweep {
+            size,
+            randomness,
+            seed,
+            max_generations,
+            threads,
+        }) => {
+            let sweep = Sweep {
+                sizes: size.clone(),
+                randomness: randomness.clone(),
+                seeds: seed.clone(),
+                max_generations: *max_generations,
+            };
+            print!(
+                "{}",
+                Report::of(&sweep.run(threads.unwrap_or_else(processors))?)
+            );
+            return Ok(());
+        }
+        Some(Command::Search {
+            size,
+            randomness,
+            max_generations,
+            distance,
+            results,
+            soups,
+            threads,
+        }) => {
+            let search = Search {
+                size: *size,
+                randomness: *randomness,
+                max_generations: *max_generations,
+                distance: *distance,
+            };
+            let threads = threads.unwrap_or_else(processors);
+            return run_search(&search, results, *soups, threads);
+        }
+        Some(Command::Damage { .. }) | None => {}
+    }
 
     if args.benchmark {
-        return benchmark();
+        return benchmark(args.size.unwrap_or(100), args.threads);
     }
 
-    let ws = match args.example {
-        0 => {
-            let mut w = WorldState::new(args.size);
-            w.randomize(args.randomness);
-            w
+    let mut ws = match (&args.load, &args.scenario, args.example) {
+        (Some(path), _, _) => load(path)?,
+        (None, Some(path), _) => {
+            let mut scenario = load_scenario(path)?;
+            override_scenario(&mut scenario, &args)?;
+            let seed = match &scenario.init {
+                Some(_) => args.seed.or(scenario.seed).unwrap_or_else(random_seed),
+                None => 0,
+            };
+            scenario.world(seed)?
         }
-        1 => WorldState::example1(),
-        2 => WorldState::example2(),
-        3 => WorldState::example3(),
-        _ => {
-            panic!("There is no example with that number!");
+        (None, None, 0) => {
+            let size = args.size.unwrap_or(50);
+            let mut w = WorldState::try_with_dimensions(
+                args.width.unwrap_or(size),
+                args.height.unwrap_or(size),
+            )?;
+            let seed = args.seed.unwrap_or_else(random_seed);
+            match &args.init {
+                Some(init) => init.apply(&mut w, seed)?,
+                None => w.randomize_with_seed(args.randomness.unwrap_or(0.5), seed)?,
+            }
+            w
         }
+        (None, None, n) => WorldState::example(n)?,
     };
+    let topology = args.topology.unwrap_or(ws.topology());
 
-    if args.gui || !args.cli {
-        match run_gui(ws.clone(), args.framerate) {
-            Ok(()) => {}
-            Err(e) => {
-                panic!(
-                    "Can't run the program with a graphical
-                        interface because of the following error.
-                        Try to run it in the terminal with --cli.
-                        \n{}",
-                    e
-                );
+    for placement in &args.pattern {
+        ws.stamp(&placement.pattern, placement.x, placement.y)?;
+    }
+
+    if let Some(rule) = &args.rule {
+        ws.set_rule(rule.clone());
+    }
+    if let Some(neighbourhood) = &args.neighbourhood {
+        ws.set_neighbourhood(neighbourhood.clone());
+    }
+
+    if let Some(generations) = args.jump {
+        if ws.rule() != &Rule::default() || ws.neighbourhood() != &Neighbourhood::default() {
+            return Err(ConfigError::Jump.into());
+        }
+        if !args.unbounded && topology != Topology::Bounded {
+            return Err(ConfigError::JumpTopology(topology).into());
+        }
+        let mut hl = HashLife::from(&ws);
+        hl.jump(generations)?;
+        eprintln!("jumped to generation {}", hl.generation());
+        if args.unbounded {
+            if let Some(path) = &args.save {
+                save(&WorldState::from(&hl), path)?;
             }
+            return run(SparseWorld::from(&hl), &args);
         }
+        ws = WorldState::from(&hl);
+    }
+    ws.set_topology(topology);
+
+    if let Some(path) = &args.save {
+        save(&ws, path)?;
+    }
+
+    if let Some(Command::Damage { x, y, output, gui }) = &args.command {
+        let x = x.unwrap_or(ws.width() / 2);
+        let y = y.unwrap_or(ws.height() / 2);
+        let damaged = Damaged::new(&ws, x, y)?;
+        if *gui {
+            return run_gui(damaged, args.framerate, args.threads);
+        }
+        return write_damage(damaged, output, args.iter);
+    }
+
+    if let Some(distance) = args.census {
+        let max_generations = args.until_stable.unwrap_or(10000);
+        if ws.settle(max_generations).is_none() {
+            eprintln!("no cycle within {max_generations} generations");
+        }
+        print!("{}", census(&ws, distance));
+        return Ok(());
+    }
+
+    if let Some(path) = &args.stats {
+        let format = args.stats_format.unwrap_or(StatsFormat::of(path));
+        return write_stats(ws, path, format, &args);
+    }
+
+    if let Some(max_generations) = args.until_stable {
+        match ws.find_cycle(max_generations) {
+            Some(outcome) => println!("{outcome}"),
+            None => println!("no cycle within {max_generations} generations"),
+        }
+        return Ok(());
+    }
+
+    if args.diff {
+        run_cli_diff(ws, args.iter, args.framerate, args.threads);
+        return Ok(());
+    }
+
+    if args.unbounded {
+        run(SparseWorld::from(&ws), &args)
+    } else {
+        run(ws, &args)
+    }
+}
+
+/// Load a world from the file at `path`.
+fn load(path: &Path) -> Result<WorldState, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("can't read the file {}: {e}", path.display()))?;
+    Format::of(path)
+        .parse(&content)
+        .map_err(|e| format!("can't load the file {}: {e}", path.display()))
+}
+
+/// Draw a random seed, printed on the standard error so that the world can
+/// be run again without mixing it with the output of the program.
+fn random_seed() -> u64 {
+    let seed = rand::random();
+    eprintln!("seed: {seed}");
+    seed
+}
+
+/// Load a scenario from the JSON file at `path`.
+fn load_scenario(path: &Path) -> Result<Scenario, String> {
+    fs::read_to_string(path)
+        .map_err(|e| format!("can't read the file {}: {e}", path.display()))?
+        .parse()
+        .map_err(|e| format!("can't load the scenario {}: {e}", path.display()))
+}
+
+/// Change `scenario` with the dimensions and the generator given on the
+/// command line, which take precedence over the ones of the file. With
+/// `--randomness`, the world is filled with exactly that proportion of
+/// **Alive** cells, as a random world is.
+fn override_scenario(scenario: &mut Scenario, args: &Args) -> Result<(), ConfigError> {
+    if let Some(size) = args.size {
+        (scenario.width, scenario.height) = (size, size);
+    }
+    scenario.width = args.width.unwrap_or(scenario.width);
+    scenario.height = args.height.unwrap_or(scenario.height);
+    if let Some(init) = &args.init {
+        scenario.init = Some(init.clone());
+    } else if let Some(randomness) = args.randomness {
+        if !(0.0..=1.0).contains(&randomness) {
+            return Err(ConfigError::Rate(randomness));
+        }
+        scenario.init = Some(Init::Soup(Soup {
+            alive: randomness,
+            mode: Mode::Exact,
+            ..Soup::default()
+        }));
+    }
+    Ok(())
+}
+
+/// Save a world in the file at `path`.
+fn save(ws: &WorldState, path: &Path) -> Result<(), String> {
+    fs::write(path, Format::of(path).write(ws))
+        .map_err(|e| format!("can't save the file {}: {e}", path.display()))
+}
+
+/// Run `ws` for `--iter` generations, and write the statistics of each
+/// generation in `format` to the file at `path`, or to the standard output
+/// if `path` is `-`.
+fn write_stats(
+    mut ws: WorldState,
+    path: &Path,
+    format: StatsFormat,
+    args: &Args,
+) -> Result<(), Box<dyn Error>> {
+    let error = |e: io::Error| format!("can't write the statistics to {}: {e}", path.display());
+    let mut out: BufWriter<Box<dyn Write>> = BufWriter::new(if path == Path::new("-") {
+        Box::new(io::stdout())
+    } else {
+        Box::new(File::create(path).map_err(error)?)
+    });
+    if let Some(header) = format.header() {
+        writeln!(out, "{header}").map_err(error)?;
+    }
+    writeln!(out, "{}", format.write(&Stats::of(&ws, None, 0))).map_err(error)?;
+    for generation in 1..=u64::from(args.iter) {
+        let previous = ws.clone();
+        ws.next_parallel(args.threads);
+        let stats = Stats::of(&ws, Some(&previous), generation);
+        writeln!(out, "{}", format.write(&stats)).map_err(error)?;
+    }
+    out.flush().map_err(error)?;
+    Ok(())
+}
+
+/// Run `damaged` for `iteration` generations, and write how much its copies
+/// differ at each generation as CSV to the file at `path`, or to the
+/// standard output if `path` is `-`.
+fn write_damage(mut damaged: Damaged, path: &Path, iteration: u16) -> Result<(), Box<dyn Error>> {
+    let error = |e: io::Error| format!("can't write the damage to {}: {e}", path.display());
+    let mut out: BufWriter<Box<dyn Write>> = BufWriter::new(if path == Path::new("-") {
+        Box::new(io::stdout())
+    } else {
+        Box::new(File::create(path).map_err(error)?)
+    });
+    writeln!(out, "{}", Spread::CSV_HEADER).map_err(error)?;
+    writeln!(out, "{}", damaged.spread().to_csv()).map_err(error)?;
+    for _ in 0..iteration {
+        damaged.next();
+        writeln!(out, "{}", damaged.spread().to_csv()).map_err(error)?;
+    }
+    out.flush().map_err(error)?;
+    Ok(())
+}
+
+/// Return the number of processors, or 1 if it's unknown.
+fn processors() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Run `search` on `soups` soups, or until it's interrupted, resuming
+/// after the soups recorded in the results file at `path`, to which the
+/// results are appended.
+fn run_search(
+    search: &Search,
+    path: &Path,
+    soups: Option<u64>,
+    threads: usize,
+) -> Result<(), Box<dyn Error>> {
+    let error = |e: io::Error| format!("can't write the results to {}: {e}", path.display());
+    let log = match fs::read_to_string(path) {
+        Ok(log) => log,
+        Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("can't read the file {}: {e}", path.display()).into()),
+    };
+    let resumed = search
+        .resume(&log)
+        .map_err(|e| format!("can't resume the search from {}: {e}", path.display()))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(error)?;
+    if resumed.is_none() {
+        writeln!(file, "{}", search.header()).map_err(error)?;
+    }
+
+    let mut seed = resumed.unwrap_or(0);
+    let end = soups.map_or(u64::MAX, |soups| seed.saturating_add(soups));
+    println!("searching from seed {seed}");
+    // the soups are searched in batches, each one being recorded at once.
+    let batch = threads as u64 * 16;
+    while seed < end {
+        let next = seed.saturating_add(batch).min(end);
+        let mut lines = String::new();
+        for discovery in search.run(seed..next, threads)? {
+            println!("{discovery} ({})", discovery.object.velocity());
+            lines.push_str(&format!("{discovery}\n"));
+        }
+        lines.push_str(&format!("done {next}\n"));
+        file.write_all(lines.as_bytes()).map_err(error)?;
+        seed = next;
+    }
+    println!("searched up to seed {seed}");
+    Ok(())
+}
+
+/// Stand in for the graphical interface when the program is built without
+/// the `gui` feature.
+#[cfg(not(feature = "gui"))]
+fn run_gui<A>(_ws: A, _framerate: u64, _threads: usize) -> Result<(), Box<dyn Error>> {
+    Err("the program was built without the `gui` feature".into())
+}
+
+/// Run the cellular automaton in the viewing modes selected by `args`.
+fn run<A: Automaton + Clone + 'static>(ws: A, args: &Args) -> Result<(), Box<dyn Error>> {
+    if args.gui || !args.cli {
+        run_gui(ws.clone(), args.framerate, args.threads).map_err(|e| {
+            format!(
+                "can't run the program with a graphical interface, \
+                 try to run it in the terminal with --cli: {e}"
+            )
+        })?;
     }
 
     if args.cli {
-        run_cli(ws, args.iter, args.framerate);
+        run_cli(ws, args.iter, args.framerate, args.threads);
     }
+    Ok(())
 }
 
 /// Run the cellular automaton in the terminal.
-fn run_cli(mut ws: WorldState, iteration: u16, framerate: u64) {
+fn run_cli<A: Automaton>(mut ws: A, iteration: u16, framerate: u64, threads: usize) {
     print!("{esc}[2J{esc}[1;1H", esc = 27 as char);
     println!("{}", ws);
     thread::sleep(Duration::from_millis(framerate));
     for _ in 0..iteration {
-        ws.next();
+        ws.step(threads);
         print!("{esc}[2J{esc}[1;1H", esc = 27 as char);
         println!("{}", ws);
         thread::sleep(Duration::from_millis(framerate));
     }
 }
 
-fn benchmark() {
+/// Run the world in the terminal as `run_cli()` does, the cells which
+/// changed since the previous generation being highlighted.
+fn run_cli_diff(mut ws: WorldState, iteration: u16, framerate: u64, threads: usize) {
+    print!("{esc}[2J{esc}[1;1H", esc = 27 as char);
+    println!("{}", ws);
+    thread::sleep(Duration::from_millis(framerate));
+    for _ in 0..iteration {
+        let previous = ws.clone();
+        ws.next_parallel(threads);
+        let diff = previous
+            .diff(&ws)
+            .expect("the generations have the same dimensions");
+        print!("{esc}[2J{esc}[1;1H", esc = 27 as char);
+        println!("{}", diff.highlight(&ws));
+        println!("{} cells changed", diff.changes.len());
+        thread::sleep(Duration::from_millis(framerate));
+    }
+}
+
+fn benchmark(size: usize, threads: usize) -> Result<(), Box<dyn Error>> {
+    // the size is checked once, to keep the checks out of the timings.
+    WorldState::try_with_dimensions(size, size)?;
+
     let mut sum_new = Duration::ZERO;
     let mut sum_randomize = Duration::ZERO;
     let mut sum_next = Duration::ZERO;
+    let mut sum_bit_next = Duration::ZERO;
+    let mut sum_next_parallel = Duration::ZERO;
+    let mut sum_bit_next_parallel = Duration::ZERO;
 
     for _ in 0..100 {
         let before_new = Instant::now();
-        let mut ws = WorldState::new(100);
+        let mut ws = WorldState::new(size);
         sum_new += before_new.elapsed();
 
         let before_randomize = Instant::now();
-        ws.randomize(0.5);
+        ws.randomize(0.5)?;
         sum_randomize += before_randomize.elapsed();
 
+        let mut bw = BitWorld::from(&ws);
+        let mut ws_parallel = ws.clone();
+        let mut bw_parallel = bw.clone();
+
         let before_next = Instant::now();
         for _ in 0..100 {
             ws.next();
         }
         sum_next += before_next.elapsed();
+
+        let before_bit_next = Instant::now();
+        for _ in 0..100 {
+            bw.next();
+        }
+        sum_bit_next += before_bit_next.elapsed();
+
+        if threads > 1 {
+            let before_next_parallel = Instant::now();
+            for _ in 0..100 {
+                ws_parallel.next_parallel(threads);
+            }
+            sum_next_parallel += before_next_parallel.elapsed();
+
+            let before_bit_next_parallel = Instant::now();
+            for _ in 0..100 {
+                bw_parallel.next_parallel(threads);
+            }
+            sum_bit_next_parallel += before_bit_next_parallel.elapsed();
+        }
     }
 
-    println!("Benchmark - 100 runs average");
+    println!("Benchmark - 100 runs average ({size}x{size})");
     println!("WorldState::new()        \t{:?}", sum_new / 100);
     println!("WorldState::randomize()  \t{:?}", sum_randomize / 100);
     println!("WorldState::next() (x100)\t{:?}", sum_next / 100);
@@ -150,4 +775,17 @@ fn benchmark() {
         "total:                   \t{:?}",
         (sum_new + sum_randomize + sum_next) / 100
     );
+    println!("BitWorld::next() (x100)  \t{:?}", sum_bit_next / 100);
+    if threads > 1 {
+        println!("\nWith {threads} threads");
+        println!(
+            "WorldState::next_parallel() (x100)\t{:?}",
+            sum_next_parallel / 100
+        );
+        println!(
+            "BitWorld::next_parallel() (x100)  \t{:?}",
+            sum_bit_next_parallel / 100
+        );
+    }
+    Ok(())
 }
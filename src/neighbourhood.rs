@@ -0,0 +1,282 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The largest radius of a neighbourhood, and the largest distance, on a
+/// row or a column, between a cell and its neighbours, so that the
+/// neighbourhood of a single cell stays small (10 200 cells at most).
+pub const MAX_RADIUS: usize = 50;
+
+/// The cells whose states are counted to compute the next state of a cell.
+///
+/// The number of **Alive** neighbours given by a rule is the number of
+/// **Alive** cells in the neighbourhood.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Neighbourhood {
+    /// The cells of the square of `2r + 1` cells around the cell,
+    /// `r` being the radius: the 8 surrounding cells for a radius of 1.
+    Moore(usize),
+
+    /// The cells at a Manhattan distance up to the radius from the cell:
+    /// the 4 orthogonal cells for a radius of 1.
+    VonNeumann(usize),
+
+    /// The 6 cells around a cell of a hexagonal grid, whose odd rows are
+    /// shifted by half a cell to the right.
+    Hexagonal,
+
+    /// The cells at the given (column, row) offsets from the cell.
+    Custom(Vec<(isize, isize)>),
+}
+
+/// The reasons why a neighbourhood can't be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseNeighbourhoodError {
+    /// The neighbourhood isn't one of the known kinds.
+    Kind(String),
+
+    /// The radius isn't a number between 1 and `MAX_RADIUS`.
+    Radius(String),
+
+    /// An offset isn't written `column,row`, is farther than `MAX_RADIUS`
+    /// or is given twice.
+    Offset(String),
+}
+
+impl fmt::Display for ParseNeighbourhoodError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseNeighbourhoodError::Kind(kind) => write!(
+                f,
+                "`{kind}` isn't one of `moore`, `von-neumann`, `hexagonal` or `custom`"
+            ),
+            ParseNeighbourhoodError::Radius(radius) => {
+                write!(f, "`{radius}` isn't a radius between 1 and {MAX_RADIUS}")
+            }
+            ParseNeighbourhoodError::Offset(offset) => {
+                write!(
+                    f,
+                    "`{offset}` isn't an offset `column,row` up to {MAX_RADIUS} cells away, given once"
+                )
+            }
+        }
+    }
+}
+
+impl Error for ParseNeighbourhoodError {}
+
+impl Default for Neighbourhood {
+    /// Return the neighbourhood of Brian's Brain, the Moore neighbourhood
+    /// of radius 1.
+    fn default() -> Neighbourhood {
+        Neighbourhood::Moore(1)
+    }
+}
+
+impl fmt::Display for Neighbourhood {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Neighbourhood::Moore(1) => write!(f, "moore"),
+            Neighbourhood::Moore(r) => write!(f, "moore:{r}"),
+            Neighbourhood::VonNeumann(1) => write!(f, "von-neumann"),
+            Neighbourhood::VonNeumann(r) => write!(f, "von-neumann:{r}"),
+            Neighbourhood::Hexagonal => write!(f, "hexagonal"),
+            Neighbourhood::Custom(offsets) => {
+                let offsets: Vec<String> = offsets
+                    .iter()
+                    .map(|(dx, dy)| format!("{dx},{dy}"))
+                    .collect();
+                write!(f, "custom:{}", offsets.join(";"))
+            }
+        }
+    }
+}
+
+impl FromStr for Neighbourhood {
+    type Err = ParseNeighbourhoodError;
+
+    /// Parse a neighbourhood written `moore`, `von-neumann`, `hexagonal`,
+    /// the two first ones being optionally followed by a radius
+    /// (e.g. `moore:2`), or `custom:` followed by offsets separated by
+    /// semicolons (e.g. `custom:-1,0;1,0;0,-2`). The radius and the offsets
+    /// can't be larger than `MAX_RADIUS`.
+    fn from_str(s: &str) -> Result<Neighbourhood, ParseNeighbourhoodError> {
+        let (kind, parameter) = match s.trim().split_once(':') {
+            Some((kind, parameter)) => (kind, Some(parameter)),
+            None => (s.trim(), None),
+        };
+        let radius = || match parameter {
+            None => Ok(1),
+            Some(radius) => radius
+                .parse()
+                .ok()
+                .filter(|radius| (1..=MAX_RADIUS).contains(radius))
+                .ok_or_else(|| ParseNeighbourhoodError::Radius(radius.to_string())),
+        };
+
+        match (kind.to_ascii_lowercase().as_str(), parameter) {
+            ("moore", _) => Ok(Neighbourhood::Moore(radius()?)),
+            ("von-neumann", _) => Ok(Neighbourhood::VonNeumann(radius()?)),
+            ("hexagonal", None) => Ok(Neighbourhood::Hexagonal),
+            ("custom", Some(offsets)) => {
+                let mut seen = HashSet::new();
+                offsets
+                    .split(';')
+                    .map(|offset| {
+                        let error = || ParseNeighbourhoodError::Offset(offset.to_string());
+                        let (dx, dy) = offset.split_once(',').ok_or_else(error)?;
+                        let offset: (isize, isize) = (
+                            dx.trim().parse().map_err(|_| error())?,
+                            dy.trim().parse().map_err(|_| error())?,
+                        );
+                        let reach = offset.0.unsigned_abs().max(offset.1.unsigned_abs());
+                        if reach <= MAX_RADIUS && seen.insert(offset) {
+                            Ok(offset)
+                        } else {
+                            Err(error())
+                        }
+                    })
+                    .collect::<Result<_, _>>()
+                    .map(Neighbourhood::Custom)
+            }
+            _ => Err(ParseNeighbourhoodError::Kind(s.to_string())),
+        }
+    }
+}
+
+impl Neighbourhood {
+    /// Return the (column, row) offsets of the neighbours of a cell, in
+    /// row-major order. The cell is on an odd row if `odd_row` is true,
+    /// which only matters for the hexagonal neighbourhood.
+    pub fn offsets(&self, odd_row: bool) -> Vec<(isize, isize)> {
+        let square = |r: usize, keep: &dyn Fn(isize, isize) -> bool| {
+            let r = r as isize;
+            (-r..=r)
+                .flat_map(|dy| (-r..=r).map(move |dx| (dx, dy)))
+                .filter(|&(dx, dy)| (dx, dy) != (0, 0) && keep(dx, dy))
+                .collect()
+        };
+
+        match self {
+            Neighbourhood::Moore(r) => square(*r, &|_, _| true),
+            Neighbourhood::VonNeumann(r) => {
+                square(*r, &|dx, dy| dx.unsigned_abs() + dy.unsigned_abs() <= *r)
+            }
+            Neighbourhood::Hexagonal => {
+                // the cells above and below are shifted towards the side
+                // the row of the cell is shifted to.
+                let shift = odd_row as isize;
+                vec![
+                    (shift - 1, -1),
+                    (shift, -1),
+                    (-1, 0),
+                    (1, 0),
+                    (shift - 1, 1),
+                    (shift, 1),
+                ]
+            }
+            Neighbourhood::Custom(offsets) => offsets.clone(),
+        }
+    }
+
+    /// Return the number of neighbours of a cell.
+    pub fn size(&self) -> usize {
+        self.offsets(false).len()
+    }
+
+    /// Return the largest distance, on a row or a column, between a cell
+    /// and its neighbours.
+    pub fn reach(&self) -> usize {
+        self.offsets(false)
+            .iter()
+            .chain(&self.offsets(true))
+            .map(|(dx, dy)| dx.unsigned_abs().max(dy.unsigned_abs()))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse_display_round_trip() {
+        for neighbourhood in [
+            "moore",
+            "moore:3",
+            "von-neumann",
+            "von-neumann:2",
+            "hexagonal",
+            "custom:-1,0;1,0;0,-2",
+        ] {
+            assert_eq!(
+                neighbourhood.parse::<Neighbourhood>().unwrap().to_string(),
+                neighbourhood
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "hex".parse::<Neighbourhood>(),
+            Err(ParseNeighbourhoodError::Kind("hex".to_string()))
+        );
+        assert_eq!(
+            "moore:0".parse::<Neighbourhood>(),
+            Err(ParseNeighbourhoodError::Radius("0".to_string()))
+        );
+        assert_eq!(
+            "moore:51".parse::<Neighbourhood>(),
+            Err(ParseNeighbourhoodError::Radius("51".to_string()))
+        );
+        assert_eq!(
+            "von-neumann:99999999999".parse::<Neighbourhood>(),
+            Err(ParseNeighbourhoodError::Radius("99999999999".to_string()))
+        );
+        assert_eq!(
+            "custom:1,0;0,-51".parse::<Neighbourhood>(),
+            Err(ParseNeighbourhoodError::Offset("0,-51".to_string()))
+        );
+        assert_eq!(
+            "custom:1,0;1".parse::<Neighbourhood>(),
+            Err(ParseNeighbourhoodError::Offset("1".to_string()))
+        );
+        assert_eq!(
+            "custom:1,0;1,0".parse::<Neighbourhood>(),
+            Err(ParseNeighbourhoodError::Offset("1,0".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_number_of_neighbours() {
+        assert_eq!(Neighbourhood::Moore(1).size(), 8);
+        assert_eq!(Neighbourhood::Moore(2).size(), 24);
+        assert_eq!(Neighbourhood::VonNeumann(1).size(), 4);
+        assert_eq!(Neighbourhood::VonNeumann(2).size(), 12);
+        assert_eq!(Neighbourhood::Hexagonal.size(), 6);
+        assert_eq!(Neighbourhood::Custom(vec![(0, 3)]).size(), 1);
+    }
+
+    #[test]
+    fn test_hexagonal_offsets_depend_on_row() {
+        assert_eq!(
+            Neighbourhood::Hexagonal.offsets(false),
+            vec![(-1, -1), (0, -1), (-1, 0), (1, 0), (-1, 1), (0, 1)]
+        );
+        assert_eq!(
+            Neighbourhood::Hexagonal.offsets(true),
+            vec![(0, -1), (1, -1), (-1, 0), (1, 0), (0, 1), (1, 1)]
+        );
+    }
+
+    #[test]
+    fn test_reach() {
+        assert_eq!(Neighbourhood::Moore(3).reach(), 3);
+        assert_eq!(Neighbourhood::Hexagonal.reach(), 1);
+        assert_eq!(Neighbourhood::Custom(vec![(-5, 2), (0, 1)]).reach(), 5);
+    }
+}
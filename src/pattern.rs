@@ -0,0 +1,498 @@
+use crate::neighbourhood::Neighbourhood;
+use crate::rule::Rule;
+use crate::world_state::sparse_world::{SparseWorld, Viewport};
+use crate::world_state::{CellState, WorldState};
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The period of an oscillator or a spaceship, and the columns and rows it
+/// moves by each period.
+type Cycle = (u64, i64, i64);
+
+/// The patterns known by name, written as `WorldState`'s `Display` does,
+/// with the kind of pattern they are and, for an oscillator or a
+/// spaceship, its cycle. They all follow the rule and the neighbourhood of
+/// Brian's Brain: small oscillators and spaceships, most of them left by
+/// random soups, a puffer and a rake.
+const CATALOGUE: &[(&str, &str, Option<Cycle>, &str)] = &[
+    // a period-3 oscillator, as spawned by `spawn_osc3()`.
+    (
+        "osc3",
+        "oscillator",
+        Some((3, 0, 0)),
+        ".O..\n.XXO\nOXX.\n..O.\n",
+    ),
+    // a period-3 oscillator of 20 cells, the same once turned by a
+    // quarter of a turn.
+    (
+        "osc3b",
+        "oscillator",
+        Some((3, 0, 0)),
+        ".....O.\nOX.OXX.\n.X.X...\n.OX.XO.\n...X.X.\n.XXO.XO\n.O.....\n",
+    ),
+    // a period-6 oscillator of 40 cells, the same once turned or mirrored.
+    (
+        "osc6",
+        "oscillator",
+        Some((6, 0, 0)),
+        "...OOO...\n.OXXOXXO.\n.X..O..X.\nOX.....XO\nOOO...OOO\nOX.....XO\n.X..O..X.\n.OXXOXXO.\n...OOO...\n",
+    ),
+    // the smallest spaceship, moving down.
+    ("glider", "spaceship", Some((1, 0, 1)), "XX\nOO\n"),
+    // a spaceship of 6 cells, moving left.
+    ("ship6", "spaceship", Some((1, -1, 0)), "..OX\nOX..\nOX..\n"),
+    // another spaceship of 6 cells, moving down.
+    ("ship6b", "spaceship", Some((1, 0, 1)), "..X\nXXO\nOO.\n"),
+    // a spaceship of 8 cells, moving left.
+    (
+        "ship8",
+        "spaceship",
+        Some((1, -1, 0)),
+        "..OX\nOX..\nOX..\n..OX\n",
+    ),
+    // another spaceship of 8 cells, moving up.
+    (
+        "ship8b",
+        "spaceship",
+        Some((1, 0, -1)),
+        ".OO.\nOXXO\nX..X\n",
+    ),
+    // a third spaceship of 8 cells, moving right.
+    (
+        "ship8c",
+        "spaceship",
+        Some((1, 1, 0)),
+        "....XO\nXO..XO\n..XO..\n",
+    ),
+    // a spaceship of 10 cells, moving down.
+    (
+        "ship10",
+        "spaceship",
+        Some((1, 0, 1)),
+        "....X\nX..XO\nOXXO.\n.OO..\n",
+    ),
+    // a period-2 spaceship of 15 cells, moving right by 2 cells.
+    (
+        "ship15",
+        "spaceship",
+        Some((2, 2, 0)),
+        ".......XO\n.X..XO.XO\nO..OO.XO.\n.X..XO...\n",
+    ),
+    // the diagonal spaceship, moving down and right by 1 cell every 4
+    // generations.
+    (
+        "diagonal",
+        "spaceship",
+        Some((4, 1, 1)),
+        "..OX\n.X..\nOXO.\n",
+    ),
+    // burns into a wick on both sides, as spawned by `spawn_wick3()`.
+    ("wick", "puffer", None, "OOO\nO.O\n.X.\n"),
+    // moves down, leaving a `ship8` moving left every 8 generations.
+    (
+        "rake8",
+        "rake",
+        None,
+        "O.O..\n....O\n.XO..\n.X...\nO.XOO\n.....\n..X..\n",
+    ),
+];
+
+/// A pattern of cells, which can be moved and turned before being
+/// stamped in a world.
+///
+/// The cells are given by their column and row relative to the origin of
+/// the pattern, the **Dead** cells being left out.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Pattern {
+    /// The cells which aren't **Dead**, row by row.
+    cells: Vec<(i64, i64, CellState)>,
+}
+
+/// The reasons why the placement of a pattern can't be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParsePlacementError {
+    /// The pattern isn't in the catalogue.
+    Name(String),
+
+    /// The position isn't written `x,y`.
+    Position(String),
+
+    /// The rotation isn't 0, 90, 180 or 270 degrees.
+    Rotation(String),
+}
+
+impl fmt::Display for ParsePlacementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParsePlacementError::Name(name) => write!(
+                f,
+                "`{name}` isn't a pattern of the catalogue: {}",
+                Pattern::catalogue().collect::<Vec<_>>().join(", ")
+            ),
+            ParsePlacementError::Position(position) => {
+                write!(f, "`{position}` isn't a position `x,y`")
+            }
+            ParsePlacementError::Rotation(rotation) => {
+                write!(f, "`{rotation}` isn't a rotation of 0, 90, 180 or 270")
+            }
+        }
+    }
+}
+
+impl Error for ParsePlacementError {}
+
+impl From<&WorldState> for Pattern {
+    /// Return the cells of `ws` which aren't **Dead**, the origin of the
+    /// pattern being the top left corner of the world.
+    fn from(ws: &WorldState) -> Pattern {
+        Pattern::new(
+            ws.iter()
+                .map(|(x, y, cell)| (x as i64, y as i64, cell))
+                .collect(),
+        )
+    }
+}
+
+impl Pattern {
+    /// Create a pattern from its cells, given as (column, row, state)
+    /// relative to the origin of the pattern.
+    pub fn new(mut cells: Vec<(i64, i64, CellState)>) -> Pattern {
+        cells.retain(|&(_, _, cell)| cell != CellState::Dead);
+        cells.sort_by_key(|&(x, y, _)| (y, x));
+        cells.dedup_by_key(|&mut (x, y, _)| (x, y));
+        Pattern { cells }
+    }
+
+    /// Return the pattern of the catalogue called `name`, or `None` if
+    /// there's none.
+    pub fn named(name: &str) -> Option<Pattern> {
+        CATALOGUE
+            .iter()
+            .find(|(n, _, _, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, _, _, cells)| {
+                let ws: WorldState = cells.parse().expect("the catalogue is valid");
+                Pattern::from(&ws)
+            })
+    }
+
+    /// Return the names of the patterns of the catalogue.
+    pub fn catalogue() -> impl Iterator<Item = &'static str> {
+        CATALOGUE.iter().map(|&(name, _, _, _)| name)
+    }
+
+    /// Return the kind of the pattern of the catalogue called `name`:
+    /// `oscillator`, `spaceship`, `puffer` or `rake`.
+    pub fn kind(name: &str) -> Option<&'static str> {
+        CATALOGUE
+            .iter()
+            .find(|(n, _, _, _)| n.eq_ignore_ascii_case(name))
+            .map(|&(_, kind, _, _)| kind)
+    }
+
+    /// Return the cells of the pattern which aren't **Dead**, as
+    /// (column, row, state), row by row.
+    pub fn cells(&self) -> &[(i64, i64, CellState)] {
+        &self.cells
+    }
+
+    /// Return the top left corner of the rectangle around the cells,
+    /// or the origin if the pattern is empty.
+    pub fn corner(&self) -> (i64, i64) {
+        let x = self.cells.iter().map(|&(x, _, _)| x).min();
+        let y = self.cells.iter().map(|&(_, y, _)| y).min();
+        (x.unwrap_or(0), y.unwrap_or(0))
+    }
+
+    /// Return the pattern with each cell moved by `f`, the rectangle around
+    /// the cells keeping its top left corner.
+    fn transform(&self, f: impl Fn(i64, i64) -> (i64, i64)) -> Pattern {
+        let (x0, y0) = self.corner();
+        let moved = Pattern::new(
+            self.cells
+                .iter()
+                .map(|&(x, y, cell)| {
+                    let (x, y) = f(x, y);
+                    (x, y, cell)
+                })
+                .collect(),
+        );
+        let (x1, y1) = moved.corner();
+        moved.translate(x0 - x1, y0 - y1)
+    }
+
+    /// Return the pattern turned clockwise by `quarter_turns` quarters
+    /// of a turn.
+    pub fn rotate(&self, quarter_turns: u8) -> Pattern {
+        (0..quarter_turns % 4).fold(self.clone(), |pattern, _| pattern.transform(|x, y| (-y, x)))
+    }
+
+    /// Return the pattern mirrored from left to right.
+    pub fn flip_horizontal(&self) -> Pattern {
+        self.transform(|x, y| (-x, y))
+    }
+
+    /// Return the pattern mirrored from top to bottom.
+    pub fn flip_vertical(&self) -> Pattern {
+        self.transform(|x, y| (x, -y))
+    }
+
+    /// Return the pattern moved by `dx` columns and `dy` rows.
+    pub fn translate(&self, dx: i64, dy: i64) -> Pattern {
+        Pattern {
+            cells: self
+                .cells
+                .iter()
+                .map(|&(x, y, cell)| (x + dx, y + dy, cell))
+                .collect(),
+        }
+    }
+
+    /// Return the pattern as it is after `generations` generations of
+    /// `rule` in `neighbourhood`, in a world without edges. The cells keep
+    /// their position relative to the origin, so a spaceship moves away
+    /// from it.
+    pub fn advance(&self, generations: u64, rule: &Rule, neighbourhood: &Neighbourhood) -> Pattern {
+        let mut sw = SparseWorld::new(Viewport {
+            x: 0,
+            y: 0,
+            width: 1,
+            height: 1,
+        });
+        sw.set_rule(rule.clone());
+        sw.set_neighbourhood(neighbourhood.clone());
+        for &(x, y, cell) in &self.cells {
+            sw.set(x, y, cell);
+        }
+        for _ in 0..generations {
+            sw.next();
+        }
+        Pattern::new(sw.cells().collect())
+    }
+}
+
+/// A pattern of the catalogue, turned and placed at a position of a world.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Placement {
+    /// The pattern, already turned.
+    pub pattern: Pattern,
+
+    /// The column of the origin of the pattern.
+    pub x: i64,
+
+    /// The row of the origin of the pattern.
+    pub y: i64,
+}
+
+impl FromStr for Placement {
+    type Err = ParsePlacementError;
+
+    /// Parse a placement written `name@x,y`, optionally followed by a
+    /// clockwise rotation in degrees (e.g. `glider@10,4,90`).
+    fn from_str(s: &str) -> Result<Placement, ParsePlacementError> {
+        let position_error = || ParsePlacementError::Position(s.to_string());
+        let (name, position) = s.trim().split_once('@').ok_or_else(position_error)?;
+        let pattern =
+            Pattern::named(name).ok_or_else(|| ParsePlacementError::Name(name.to_string()))?;
+
+        let mut parts = position.split(',').map(str::trim);
+        let mut coordinate = || {
+            parts
+                .next()
+                .and_then(|n| n.parse().ok())
+                .ok_or_else(position_error)
+        };
+        let (x, y) = (coordinate()?, coordinate()?);
+        let quarter_turns = match parts.next() {
+            None | Some("0") => 0,
+            Some("90") => 1,
+            Some("180") => 2,
+            Some("270") => 3,
+            Some(rotation) => return Err(ParsePlacementError::Rotation(rotation.to_string())),
+        };
+        match parts.next() {
+            None => Ok(Placement {
+                pattern: pattern.rotate(quarter_turns),
+                x,
+                y,
+            }),
+            Some(_) => Err(position_error()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::world_state::Spawn;
+
+    /// Return the pattern of the catalogue called `name`.
+    fn named(name: &str) -> Pattern {
+        Pattern::named(name).unwrap()
+    }
+
+    #[test]
+    fn test_catalogue_patterns_are_valid() {
+        for name in Pattern::catalogue() {
+            assert!(!named(name).cells().is_empty(), "{name}");
+            assert!(Pattern::kind(name).is_some(), "{name}");
+        }
+        assert_eq!(Pattern::named("unknown"), None);
+    }
+
+    #[test]
+    fn test_catalogue_matches_the_spawners() {
+        let mut ws = WorldState::new(4);
+        ws.spawn_osc3(0, 0).unwrap();
+        assert_eq!(Pattern::from(&ws), named("osc3"));
+
+        let mut ws = WorldState::new(2);
+        ws.spawn_glider4_downward(0, 0).unwrap();
+        assert_eq!(Pattern::from(&ws), named("glider"));
+    }
+
+    #[test]
+    fn test_rotations_of_the_glider() {
+        let glider = named("glider");
+        for (quarter_turns, spawn) in [
+            (
+                0,
+                Spawn::spawn_glider4_downward as fn(&mut WorldState, i64, i64) -> _,
+            ),
+            (1, Spawn::spawn_glider4_leftward),
+            (2, Spawn::spawn_glider4_upward),
+            (3, Spawn::spawn_glider4_rightward),
+        ] {
+            let mut ws = WorldState::new(2);
+            spawn(&mut ws, 0, 0).unwrap();
+            assert_eq!(glider.rotate(quarter_turns), Pattern::from(&ws));
+        }
+        assert_eq!(glider.rotate(4), glider);
+    }
+
+    #[test]
+    fn test_flips_and_translation() {
+        let osc3 = named("osc3");
+        assert_eq!(osc3.flip_horizontal().flip_horizontal(), osc3);
+        assert_eq!(osc3.flip_horizontal().flip_vertical(), osc3.rotate(2));
+        let moved = osc3.translate(3, -2);
+        assert_eq!(moved.cells()[0], (4, -2, CellState::Alive));
+        assert_eq!(moved.rotate(1).cells()[0].1, -2);
+    }
+
+    #[test]
+    fn test_advance_oscillators_and_spaceships() {
+        let (rule, neighbourhood) = (Rule::default(), Neighbourhood::default());
+        let osc3 = named("osc3");
+        assert_ne!(osc3.advance(1, &rule, &neighbourhood), osc3);
+        assert_eq!(osc3.advance(3, &rule, &neighbourhood), osc3);
+
+        let glider = named("glider");
+        assert_eq!(
+            glider.advance(5, &rule, &neighbourhood),
+            glider.translate(0, 5)
+        );
+
+        // the glider needs the diagonal neighbours to move.
+        let von_neumann = Neighbourhood::VonNeumann(1);
+        assert_eq!(glider.advance(2, &rule, &von_neumann).cells(), &[]);
+    }
+
+    #[test]
+    fn test_catalogue_periods_and_displacements() {
+        let (rule, neighbourhood) = (Rule::default(), Neighbourhood::default());
+        for &(name, kind, cycle, _) in CATALOGUE {
+            let Some((period, dx, dy)) = cycle else {
+                assert!(matches!(kind, "puffer" | "rake"), "{name}");
+                continue;
+            };
+            let pattern = named(name);
+            assert_eq!(kind == "oscillator", (dx, dy) == (0, 0), "{name}");
+            for generations in 1..period {
+                let phase = pattern.advance(generations, &rule, &neighbourhood);
+                let (x, y) = phase.corner();
+                assert_ne!(
+                    phase.translate(-x, -y),
+                    pattern,
+                    "{name} after {generations} generations"
+                );
+            }
+            assert_eq!(
+                pattern.advance(period, &rule, &neighbourhood),
+                pattern.translate(dx, dy),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_spaceships_move_at_the_speed_of_light() {
+        let (rule, neighbourhood) = (Rule::default(), Neighbourhood::default());
+        let ship8 = named("ship8");
+        assert_eq!(
+            ship8.advance(7, &rule, &neighbourhood),
+            ship8.translate(-7, 0)
+        );
+    }
+
+    #[test]
+    fn test_puffers_and_rakes_grow() {
+        let (rule, neighbourhood) = (Rule::default(), Neighbourhood::default());
+        let population = |name: &str, generations| {
+            named(name)
+                .advance(generations, &rule, &neighbourhood)
+                .cells()
+                .len()
+        };
+        assert_eq!(population("wick", 101) - population("wick", 100), 2);
+        assert_eq!(population("rake8", 72) - population("rake8", 64), 8);
+
+        // the first ship left behind by the rake.
+        let rake = named("rake8").advance(40, &rule, &neighbourhood);
+        let ship = rake.cells().iter().filter(|&&(_, y, _)| y < 10).copied();
+        let ship = Pattern::new(ship.collect());
+        let (x0, y0) = ship.corner();
+        assert_eq!(ship.translate(-x0, -y0), named("ship8"));
+    }
+
+    #[test]
+    fn test_stamp() {
+        let mut ws = WorldState::with_dimensions(5, 3);
+        let glider = named("glider").rotate(3);
+        ws.stamp(&glider, 3, 1).unwrap();
+        assert_eq!(ws.to_string(), ".....\n...XO\n...XO\n");
+        assert_eq!(
+            ws.stamp(&glider, 4, 1),
+            Err(crate::world_state::OutOfBounds { x: 5, y: 1 })
+        );
+    }
+
+    #[test]
+    fn test_parse_placement() {
+        let placement: Placement = "glider@10,4,90".parse().unwrap();
+        assert_eq!(placement.pattern, named("glider").rotate(1));
+        assert_eq!((placement.x, placement.y), (10, 4));
+        assert_eq!(
+            "OSC3@-1,2".parse::<Placement>().unwrap().pattern,
+            named("osc3")
+        );
+    }
+
+    #[test]
+    fn test_parse_placement_errors() {
+        assert_eq!(
+            "ship@1,2".parse::<Placement>(),
+            Err(ParsePlacementError::Name("ship".to_string()))
+        );
+        assert_eq!(
+            "glider@1".parse::<Placement>(),
+            Err(ParsePlacementError::Position("glider@1".to_string()))
+        );
+        assert_eq!(
+            "glider@1,2,45".parse::<Placement>(),
+            Err(ParsePlacementError::Rotation("45".to_string()))
+        );
+    }
+}
@@ -0,0 +1,359 @@
+use crate::neighbourhood::MAX_RADIUS;
+use crate::world_state::CellState;
+
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The largest number of neighbours, the one of a neighbourhood made of
+/// every cell up to `MAX_RADIUS` away, the cell itself included.
+const MAX_COUNT: usize = (2 * MAX_RADIUS + 1) * (2 * MAX_RADIUS + 1);
+
+/// A rule of the *Generations* family, to which Brian's Brain belongs.
+///
+/// A rule is written `B.../S.../C...` (Golly's notation), where:
+/// - `B` lists the numbers of **Alive** neighbours for which a **Dead**
+///   cell becomes **Alive** (birth),
+/// - `S` lists the numbers of **Alive** neighbours for which an **Alive**
+///   cell stays **Alive** (survival),
+/// - `C` is the number of states a cell can take. Besides **Alive** and
+///   **Dead**, cells go through `C - 2` stages of **Dying** before dying.
+///
+/// The numbers of neighbours are digits, or are separated by commas when
+/// the neighbourhood is large enough to count more than 9 neighbours,
+/// a range of numbers being written with a hyphen (e.g. `B3,10-12`).
+///
+/// Brian's Brain is `B2/S/C3`, the default rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rule {
+    birth: BTreeSet<usize>,
+    survival: BTreeSet<usize>,
+    states: u16,
+}
+
+/// The reasons why a rule can't be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseRuleError {
+    /// The rule doesn't have the form `B.../S.../C...`.
+    Format(String),
+
+    /// A number of neighbours, or a range of numbers, isn't valid or is
+    /// larger than the largest neighbourhood.
+    Count(String),
+
+    /// The number of states isn't a number between 2 and 256.
+    States(String),
+}
+
+impl fmt::Display for ParseRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseRuleError::Format(rule) => {
+                write!(f, "`{rule}` doesn't have the form `B.../S.../C...`")
+            }
+            ParseRuleError::Count(count) => {
+                write!(f, "`{count}` isn't a number of neighbours")
+            }
+            ParseRuleError::States(states) => {
+                write!(f, "`{states}` isn't a number of states between 2 and 256")
+            }
+        }
+    }
+}
+
+impl Error for ParseRuleError {}
+
+impl Default for Rule {
+    /// Return the rule of Brian's Brain, `B2/S/C3`.
+    fn default() -> Rule {
+        Rule {
+            birth: BTreeSet::from([2]),
+            survival: BTreeSet::new(),
+            states: 3,
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts = |set: &BTreeSet<usize>| -> String {
+            if set.iter().all(|&count| count < 10) {
+                return set.iter().map(|count| count.to_string()).collect();
+            }
+
+            // group the consecutive numbers into ranges.
+            let mut ranges: Vec<(usize, usize)> = vec![];
+            for &count in set {
+                match ranges.last_mut() {
+                    Some((_, last)) if *last + 1 == count => *last = count,
+                    _ => ranges.push((count, count)),
+                }
+            }
+            let ranges: Vec<String> = ranges
+                .into_iter()
+                .map(|(first, last)| match last - first {
+                    0 => first.to_string(),
+                    1 => format!("{first},{last}"),
+                    _ => format!("{first}-{last}"),
+                })
+                .collect();
+            ranges.join(",")
+        };
+        write!(
+            f,
+            "B{}/S{}/C{}",
+            counts(&self.birth),
+            counts(&self.survival),
+            self.states
+        )
+    }
+}
+
+impl FromStr for Rule {
+    type Err = ParseRuleError;
+
+    /// Parse a rule written `B.../S.../C...`, the parts being in any order
+    /// and their letters in any case. The older notation `S/B/C`, without
+    /// letters (e.g. `345/2/4`), is accepted as well.
+    ///
+    /// Without the number of states (e.g. `B3/S23` or `23/3`), the rule is
+    /// a *Life-like* one, whose cells are either **Alive** or **Dead**.
+    fn from_str(s: &str) -> Result<Rule, ParseRuleError> {
+        let format_error = || ParseRuleError::Format(s.to_string());
+        let parts: Vec<&str> = s.trim().split('/').collect();
+        if !(2..=3).contains(&parts.len()) {
+            return Err(format_error());
+        }
+
+        let (mut birth, mut survival, mut states) = (None, None, None);
+        if parts.iter().all(|p| {
+            p.chars()
+                .all(|c| c.is_ascii_digit() || c == ',' || c == '-')
+        }) {
+            (survival, birth, states) = (Some(parts[0]), Some(parts[1]), parts.get(2).copied());
+        } else {
+            for &part in &parts {
+                let mut chars = part.chars();
+                let slot = match chars.next().map(|c| c.to_ascii_uppercase()) {
+                    Some('B') => &mut birth,
+                    Some('S') => &mut survival,
+                    Some('C') => &mut states,
+                    _ => return Err(format_error()),
+                };
+                if slot.replace(chars.as_str()).is_some() {
+                    return Err(format_error());
+                }
+            }
+        }
+
+        if parts.len() == 2 {
+            states = states.or(Some("2"));
+        }
+        let (Some(birth), Some(survival), Some(states)) = (birth, survival, states) else {
+            return Err(format_error());
+        };
+        Ok(Rule {
+            birth: parse_counts(birth)?,
+            survival: parse_counts(survival)?,
+            states: states
+                .parse()
+                .ok()
+                .filter(|states| (2..=256).contains(states))
+                .ok_or_else(|| ParseRuleError::States(states.to_string()))?,
+        })
+    }
+}
+
+/// Parse numbers of neighbours written as a list of digits, or as
+/// numbers and ranges separated by commas.
+fn parse_counts(counts: &str) -> Result<BTreeSet<usize>, ParseRuleError> {
+    if !counts.contains([',', '-']) {
+        return counts
+            .chars()
+            .map(|c| match c.to_digit(10) {
+                Some(count) => Ok(count as usize),
+                None => Err(ParseRuleError::Count(c.to_string())),
+            })
+            .collect();
+    }
+
+    let mut set = BTreeSet::new();
+    for range in counts.split(',') {
+        let error = || ParseRuleError::Count(range.to_string());
+        let (first, last) = range.split_once('-').unwrap_or((range, range));
+        let first: usize = first.parse().map_err(|_| error())?;
+        let last: usize = last.parse().map_err(|_| error())?;
+        if first > last || last > MAX_COUNT {
+            return Err(error());
+        }
+        set.extend(first..=last);
+    }
+    Ok(set)
+}
+
+impl Rule {
+    /// Return the number of **Dying** stages a cell goes through before
+    /// being **Dead**.
+    pub fn dying_stages(&self) -> u8 {
+        (self.states - 2) as u8
+    }
+
+    /// Return the state following `cell`, `alive_neighbours` giving the
+    /// number of **Alive** neighbours of the cell. It's only called when
+    /// that number matters.
+    pub fn next_state(
+        &self,
+        cell: CellState,
+        alive_neighbours: impl FnOnce() -> usize,
+    ) -> CellState {
+        match cell {
+            CellState::Alive => {
+                if !self.survival.is_empty() && self.survival.contains(&alive_neighbours()) {
+                    CellState::Alive
+                } else if self.dying_stages() > 0 {
+                    CellState::Dying(0)
+                } else {
+                    CellState::Dead
+                }
+            }
+            CellState::Dying(stage) => match stage.checked_add(1) {
+                Some(next) if next < self.dying_stages() => CellState::Dying(next),
+                _ => CellState::Dead,
+            },
+            CellState::Dead => {
+                if self.birth.contains(&alive_neighbours()) {
+                    CellState::Alive
+                } else {
+                    CellState::Dead
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_default_is_brian_s_brain() {
+        assert_eq!(Rule::default(), "B2/S/C3".parse().unwrap());
+        assert_eq!(Rule::default().to_string(), "B2/S/C3");
+    }
+
+    #[test]
+    fn test_parse_display_round_trip() {
+        for rule in [
+            "B2/S/C3",
+            "B34/S34/C5",
+            "B2/S345/C4",
+            "B3/S23/C2",
+            "B/S012345678/C256",
+            "B3,10-12/S0,1,20/C2",
+        ] {
+            assert_eq!(rule.parse::<Rule>().unwrap().to_string(), rule);
+        }
+    }
+
+    #[test]
+    fn test_parse_other_notations() {
+        let star_wars: Rule = "B2/S345/C4".parse().unwrap();
+        assert_eq!("345/2/4".parse(), Ok(star_wars.clone()));
+        assert_eq!("s345/b2/c4".parse(), Ok(star_wars.clone()));
+        assert_eq!("C4/B2/S345".parse(), Ok(star_wars.clone()));
+        assert_eq!("B2/S3-5/C4".parse(), Ok(star_wars));
+
+        let life: Rule = "B3/S23/C2".parse().unwrap();
+        assert_eq!("B3/S23".parse(), Ok(life.clone()));
+        assert_eq!("23/3".parse(), Ok(life));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "B2".parse::<Rule>(),
+            Err(ParseRuleError::Format("B2".to_string()))
+        );
+        assert_eq!(
+            "B2/C3".parse::<Rule>(),
+            Err(ParseRuleError::Format("B2/C3".to_string()))
+        );
+        assert_eq!(
+            "B2/B3/C3".parse::<Rule>(),
+            Err(ParseRuleError::Format("B2/B3/C3".to_string()))
+        );
+        assert_eq!(
+            "B2x/S/C3".parse::<Rule>(),
+            Err(ParseRuleError::Count("x".to_string()))
+        );
+        assert_eq!(
+            "B2,5-3/S/C3".parse::<Rule>(),
+            Err(ParseRuleError::Count("5-3".to_string()))
+        );
+        assert_eq!(
+            "B2,3-10202/S/C3".parse::<Rule>(),
+            Err(ParseRuleError::Count("3-10202".to_string()))
+        );
+        assert_eq!(
+            "B2/S0-18446744073709551615/C3".parse::<Rule>(),
+            Err(ParseRuleError::Count("0-18446744073709551615".to_string()))
+        );
+        assert_eq!(
+            "B2/S/C1".parse::<Rule>(),
+            Err(ParseRuleError::States("1".to_string()))
+        );
+        assert_eq!(
+            "B2/S/C".parse::<Rule>(),
+            Err(ParseRuleError::States("".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_next_state_decay() {
+        let rule: Rule = "B2/S/C5".parse().unwrap();
+        let mut cell = CellState::Alive;
+        let mut stages = vec![];
+        for _ in 0..5 {
+            cell = rule.next_state(cell, || 0);
+            stages.push(cell);
+        }
+        assert_eq!(
+            stages,
+            vec![
+                CellState::Dying(0),
+                CellState::Dying(1),
+                CellState::Dying(2),
+                CellState::Dead,
+                CellState::Dead,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_next_state_last_stages() {
+        let rule: Rule = "B2/S/C256".parse().unwrap();
+        assert_eq!(
+            rule.next_state(CellState::Dying(252), || 0),
+            CellState::Dying(253)
+        );
+        assert_eq!(
+            rule.next_state(CellState::Dying(253), || 0),
+            CellState::Dead
+        );
+        assert_eq!(
+            rule.next_state(CellState::Dying(255), || 0),
+            CellState::Dead
+        );
+    }
+
+    #[test]
+    fn test_next_state_survival_and_birth() {
+        let rule: Rule = "B3/S23/C2".parse().unwrap();
+        assert_eq!(rule.next_state(CellState::Alive, || 2), CellState::Alive);
+        assert_eq!(rule.next_state(CellState::Alive, || 4), CellState::Dead);
+        assert_eq!(rule.next_state(CellState::Dead, || 3), CellState::Alive);
+        assert_eq!(rule.next_state(CellState::Dead, || 2), CellState::Dead);
+    }
+}
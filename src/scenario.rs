@@ -0,0 +1,299 @@
+use crate::init::Init;
+use crate::neighbourhood::Neighbourhood;
+use crate::pattern::Placement;
+use crate::rule::Rule;
+use crate::topology::Topology;
+use crate::world_state::{ConfigError, OutOfBounds, Spawn, WorldState};
+
+use serde::{Deserialize, Deserializer};
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The scenarios of `--example`, bundled in the program.
+const EXAMPLES: [&str; 3] = [
+    include_str!("../scenarios/example1.json"),
+    include_str!("../scenarios/example2.json"),
+    include_str!("../scenarios/example3.json"),
+];
+
+/// The description of a world to run, read from a JSON file such as:
+///
+/// ```json
+/// {
+///   "description": "Two gliders on a torus.",
+///   "width": 40,
+///   "height": 30,
+///   "topology": "torus",
+///   "rule": "B2/S/C3",
+///   "patterns": ["glider@10,4", "glider@20,4,90"]
+/// }
+/// ```
+///
+/// Only the dimensions are required. The values are written as with the
+/// command line options of the same name, the patterns being placements
+/// of `--pattern`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    /// What the scenario shows, and who made it.
+    #[serde(default)]
+    pub description: String,
+
+    /// The number of cells on each row of the world.
+    pub width: usize,
+
+    /// The number of rows of the world.
+    pub height: usize,
+
+    /// How the edges of the world are connected.
+    #[serde(default)]
+    pub topology: Topology,
+
+    /// How the cells evolve from one generation to the next.
+    #[serde(default, deserialize_with = "parsed")]
+    pub rule: Rule,
+
+    /// The cells counted as neighbours by the rule.
+    #[serde(default, deserialize_with = "parsed")]
+    pub neighbourhood: Neighbourhood,
+
+    /// The generator filling the world before the patterns are placed,
+    /// the world being empty without one.
+    #[serde(default, deserialize_with = "parsed_option")]
+    pub init: Option<Init>,
+
+    /// The seed from which the generator makes its random choices.
+    #[serde(default)]
+    pub seed: Option<u64>,
+
+    /// The patterns placed on the world, in order.
+    #[serde(default, deserialize_with = "parsed_list")]
+    pub patterns: Vec<Placement>,
+}
+
+/// Deserialize a value written as a string, with its `FromStr` implementation.
+fn parsed<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    let s = String::deserialize(deserializer)?;
+    s.parse().map_err(serde::de::Error::custom)
+}
+
+/// Deserialize an optional value written as a string, as `parsed()` does.
+fn parsed_option<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    parsed(deserializer).map(Some)
+}
+
+/// Deserialize a list of values written as strings, as `parsed()` does.
+fn parsed_list<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|s| s.parse().map_err(serde::de::Error::custom))
+        .collect()
+}
+
+/// The reasons why a scenario can't be read or made into a world.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScenarioError {
+    /// The file isn't JSON describing a scenario, with the reason given
+    /// by the parser.
+    Syntax(String),
+
+    /// The world can't be made with the dimensions of the scenario.
+    Config(ConfigError),
+
+    /// A pattern doesn't fit in the world.
+    Pattern(OutOfBounds),
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScenarioError::Syntax(reason) => write!(f, "the scenario is invalid: {reason}"),
+            ScenarioError::Config(e) => write!(f, "{e}"),
+            ScenarioError::Pattern(e) => write!(f, "a pattern doesn't fit: {e}"),
+        }
+    }
+}
+
+impl Error for ScenarioError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ScenarioError::Syntax(_) => None,
+            ScenarioError::Config(e) => Some(e),
+            ScenarioError::Pattern(e) => Some(e),
+        }
+    }
+}
+
+impl From<ConfigError> for ScenarioError {
+    fn from(e: ConfigError) -> ScenarioError {
+        ScenarioError::Config(e)
+    }
+}
+
+impl From<OutOfBounds> for ScenarioError {
+    fn from(e: OutOfBounds) -> ScenarioError {
+        ScenarioError::Pattern(e)
+    }
+}
+
+impl FromStr for Scenario {
+    type Err = ScenarioError;
+
+    /// Parse a scenario from the content of a JSON file.
+    fn from_str(s: &str) -> Result<Scenario, ScenarioError> {
+        serde_json::from_str(s).map_err(|e| ScenarioError::Syntax(e.to_string()))
+    }
+}
+
+impl Scenario {
+    /// Return the bundled scenario of the example with the number `n`,
+    /// from 1 to 3, or an error if there is none.
+    pub fn example(n: u16) -> Result<Scenario, ConfigError> {
+        let json = EXAMPLES
+            .get((n as usize).wrapping_sub(1))
+            .ok_or(ConfigError::Example(n))?;
+        Ok(json.parse().expect("the bundled scenarios are valid"))
+    }
+
+    /// Make the world described by the scenario: it's filled by `init`
+    /// with the random choices made from `seed`, then the patterns are
+    /// placed on it.
+    ///
+    /// The `seed` is usually the one of the scenario, when it has one.
+    pub fn world(&self, seed: u64) -> Result<WorldState, ScenarioError> {
+        let mut ws = WorldState::try_with_dimensions(self.width, self.height)?;
+        ws.set_rule(self.rule.clone());
+        ws.set_neighbourhood(self.neighbourhood.clone());
+        ws.set_topology(self.topology);
+        if let Some(init) = &self.init {
+            init.apply(&mut ws, seed)?;
+        }
+        for placement in &self.patterns {
+            ws.stamp(&placement.pattern, placement.x, placement.y)?;
+        }
+        Ok(ws)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::world_state::CellState;
+
+    #[test]
+    fn test_examples_match_the_spawners() {
+        let mut ws = WorldState::new(14);
+        for (x, y) in [(0, 0), (10, 10), (0, 10), (10, 0), (5, 5)] {
+            ws.spawn_osc3(x, y).unwrap();
+        }
+        assert_eq!(Scenario::example(1).unwrap().world(0), Ok(ws));
+
+        let mut ws = WorldState::new(100);
+        for (x, y) in [(42, 0), (30, 18), (30, 22), (13, 42)] {
+            ws.spawn_glider4_downward(x, y).unwrap();
+        }
+        for (x, y) in [(23, 57), (19, 62)] {
+            ws.spawn_glider4_leftward(x, y).unwrap();
+        }
+        for (x, y) in [(10, 68), (24, 87), (28, 93)] {
+            ws.spawn_glider4_upward(x, y).unwrap();
+        }
+        assert_eq!(Scenario::example(2).unwrap().world(0), Ok(ws));
+
+        let mut ws = WorldState::new(100);
+        ws.spawn_wick3(50, 50).unwrap();
+        assert_eq!(Scenario::example(3).unwrap().world(0), Ok(ws));
+
+        assert_eq!(Scenario::example(0), Err(ConfigError::Example(0)));
+        assert_eq!(Scenario::example(4), Err(ConfigError::Example(4)));
+    }
+
+    #[test]
+    fn test_parse_every_field() {
+        let scenario: Scenario = r#"{
+            "description": "A soup and a glider.",
+            "width": 12,
+            "height": 8,
+            "topology": "klein-bottle",
+            "rule": "B2/S345/C4",
+            "neighbourhood": "von-neumann:2",
+            "init": "soup:alive=0.5;rect=0,0,6,4",
+            "seed": 42,
+            "patterns": ["glider@10,6"]
+        }"#
+        .parse()
+        .unwrap();
+        assert_eq!(scenario.topology, Topology::KleinBottle);
+        assert_eq!(scenario.rule, "B2/S345/C4".parse().unwrap());
+        assert_eq!(scenario.seed, Some(42));
+
+        let ws = scenario.world(42).unwrap();
+        assert_eq!(ws, scenario.world(42).unwrap());
+        assert_eq!(ws.neighbourhood(), &"von-neumann:2".parse().unwrap());
+        assert_eq!(ws.get(10, 6), Ok(CellState::Dying(0)));
+        assert!(ws
+            .iter()
+            .any(|(x, y, cell)| x < 6 && y < 4 && cell != CellState::Dead));
+        assert!(ws
+            .iter()
+            .all(|(x, y, cell)| x < 6 && y < 4 || x >= 10 || cell == CellState::Dead));
+    }
+
+    #[test]
+    fn test_default_fields() {
+        let scenario: Scenario = r#"{"width": 3, "height": 2}"#.parse().unwrap();
+        assert_eq!(scenario.world(0), Ok(WorldState::with_dimensions(3, 2)));
+    }
+
+    #[test]
+    fn test_errors() {
+        let syntax_error =
+            |json: &str| matches!(json.parse::<Scenario>(), Err(ScenarioError::Syntax(_)));
+        assert!(syntax_error("{\"width\": 3"));
+        assert!(syntax_error("{\"height\": 3}"));
+        assert!(syntax_error("{\"width\": 3, \"height\": 3, \"colour\": 1}"));
+        assert!(syntax_error(
+            "{\"width\": 3, \"height\": 3, \"rule\": \"B2\"}"
+        ));
+        assert!(syntax_error(
+            "{\"width\": 3, \"height\": 3, \"topology\": \"sphere\"}"
+        ));
+        assert!(syntax_error(
+            "{\"width\": 3, \"height\": 3, \"patterns\": [\"block@0,0\"]}"
+        ));
+
+        let scenario: Scenario = "{\"width\": 0, \"height\": 3}".parse().unwrap();
+        assert_eq!(
+            scenario.world(0),
+            Err(ScenarioError::Config(ConfigError::Empty {
+                width: 0,
+                height: 3
+            }))
+        );
+        let scenario: Scenario = "{\"width\": 3, \"height\": 3, \"patterns\": [\"osc3@0,0\"]}"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            scenario.world(0),
+            Err(ScenarioError::Pattern(OutOfBounds { x: 3, y: 1 }))
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use crate::census::{objects, Object};
+use crate::world_state::cycle::Outcome;
+use crate::world_state::{ConfigError, WorldState};
+
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+use std::thread;
+
+/// A search for the objects which aren't in the pattern catalogue, among
+/// the objects left by random soups once they settled, as apgsearch does.
+///
+/// Each soup is a square world filled at random from its own seed, so that
+/// it can be run again. The results of a search are kept in a log, to which
+/// lines are only appended:
+///
+/// ```text
+/// search size=50 randomness=0.5 max-generations=10000 distance=2
+/// found 37 xp4_.OX./O..O/XOO.
+/// done 64
+/// ```
+///
+/// The first line describes the search, each `found` line gives the seed of
+/// a soup and the code of an object it left, and each `done` line gives the
+/// seed before which every soup was searched, so that the search can resume.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Search {
+    /// The size of the soups.
+    pub size: usize,
+
+    /// The proportion of **Alive** cells in the soups.
+    pub randomness: f64,
+
+    /// The number of generations after which a soup which hasn't settled
+    /// is censused anyway.
+    pub max_generations: u64,
+
+    /// The distance below which two cells belong to the same object, as
+    /// for `census::components()`.
+    pub distance: usize,
+}
+
+/// An object which isn't in the catalogue, and the soup it was found in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Discovery {
+    /// The seed of the soup.
+    pub seed: u64,
+
+    /// The object, where it was in the settled soup.
+    pub object: Object,
+}
+
+impl fmt::Display for Discovery {
+    /// Write the discovery as a `found` line of the log, without its end.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "found {} {}", self.seed, self.object.code)
+    }
+}
+
+/// The reasons why a search can't resume from a log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SearchError {
+    /// The log was written by a search with other parameters, described
+    /// by its first line.
+    Parameters(String),
+
+    /// A line of the log can't be read.
+    Line(String),
+}
+
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchError::Parameters(header) => {
+                write!(f, "the results were written by another search: `{header}`")
+            }
+            SearchError::Line(line) => write!(f, "the line `{line}` isn't a result"),
+        }
+    }
+}
+
+impl Error for SearchError {}
+
+impl Search {
+    /// Return the first line of the log of the search.
+    pub fn header(&self) -> String {
+        format!(
+            "search size={} randomness={} max-generations={} distance={}",
+            self.size, self.randomness, self.max_generations, self.distance
+        )
+    }
+
+    /// Return the seed from which the search resumes, after the soups
+    /// recorded as searched in `log`, or `None` if the log is empty.
+    ///
+    /// An error is returned if the log was written by a search with
+    /// other parameters, or can't be read.
+    pub fn resume(&self, log: &str) -> Result<Option<u64>, SearchError> {
+        let mut lines = log.lines();
+        let Some(header) = lines.next() else {
+            return Ok(None);
+        };
+        if header != self.header() {
+            return Err(SearchError::Parameters(header.to_string()));
+        }
+        let mut next = None;
+        for line in lines {
+            let error = || SearchError::Line(line.to_string());
+            match line.split_once(' ') {
+                Some(("done", seed)) => next = Some(seed.parse().map_err(|_| error())?),
+                Some(("found", _)) => {}
+                _ => return Err(error()),
+            }
+        }
+        Ok(next.or(Some(0)))
+    }
+
+    /// Return the objects which aren't in the catalogue, left by the soup
+    /// of `seed` once it settled. The objects which die when they are run
+    /// alone are left out.
+    pub fn soup(&self, seed: u64) -> Result<Vec<Object>, ConfigError> {
+        let mut ws = WorldState::try_with_dimensions(self.size, self.size)?;
+        ws.randomize_with_seed(self.randomness, seed)?;
+        ws.settle(self.max_generations);
+        Ok(objects(&ws, self.distance)
+            .into_iter()
+            .filter(|o| o.name.is_none())
+            .filter(|o| !matches!(o.outcome, Some(Outcome::Extinct { .. })))
+            .collect())
+    }
+
+    /// Search the soups of the `seeds`, with up to `threads` soups at the
+    /// same time, and return the discoveries by seed.
+    ///
+    /// An error is returned, before running any soup, if the size or the
+    /// proportion isn't valid.
+    pub fn run(&self, seeds: Range<u64>, threads: usize) -> Result<Vec<Discovery>, ConfigError> {
+        WorldState::try_with_dimensions(self.size, self.size)?
+            .randomize_with_seed(self.randomness, seeds.start)?;
+        let seeds: Vec<u64> = seeds.collect();
+        let chunk = seeds.len().div_ceil(threads.max(1)).max(1);
+        let discoveries = thread::scope(|scope| {
+            let handles: Vec<_> = seeds
+                .chunks(chunk)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        let mut discoveries = vec![];
+                        for &seed in chunk {
+                            let objects = self.soup(seed).expect("the soups are checked");
+                            discoveries.extend(
+                                objects.into_iter().map(|object| Discovery { seed, object }),
+                            );
+                        }
+                        discoveries
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("a soup panicked"))
+                .collect()
+        });
+        Ok(discoveries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn search() -> Search {
+        Search {
+            size: 16,
+            randomness: 0.3,
+            max_generations: 500,
+            distance: 2,
+        }
+    }
+
+    #[test]
+    fn test_resume() {
+        let search = search();
+        let header = search.header();
+        assert_eq!(
+            header,
+            "search size=16 randomness=0.3 max-generations=500 distance=2"
+        );
+        assert_eq!(search.resume(""), Ok(None));
+        assert_eq!(search.resume(&format!("{header}\n")), Ok(Some(0)));
+        assert_eq!(
+            search.resume(&format!("{header}\nfound 3 xp2_OX\ndone 8\ndone 16\n")),
+            Ok(Some(16))
+        );
+    }
+
+    #[test]
+    fn test_resume_another_search() {
+        let other = Search {
+            size: 32,
+            ..search()
+        };
+        assert_eq!(
+            search().resume(&format!("{}\ndone 8\n", other.header())),
+            Err(SearchError::Parameters(other.header()))
+        );
+        assert_eq!(
+            search().resume(&format!("{}\ndone eight\n", search().header())),
+            Err(SearchError::Line("done eight".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_run() {
+        let search = search();
+        let discoveries = search.run(0..12, 4).unwrap();
+        assert_eq!(discoveries, search.run(0..12, 1).unwrap());
+        assert!(discoveries.windows(2).all(|w| w[0].seed <= w[1].seed));
+        for discovery in &discoveries {
+            assert_eq!(discovery.object.name, None);
+            assert_eq!(
+                discovery.to_string(),
+                format!("found {} {}", discovery.seed, discovery.object.code)
+            );
+        }
+        assert_eq!(
+            Search {
+                randomness: 2.0,
+                ..search
+            }
+            .run(0..1, 1),
+            Err(ConfigError::Rate(2.0))
+        );
+    }
+}
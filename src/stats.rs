@@ -0,0 +1,237 @@
+use crate::world_state::{CellState, WorldState};
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use std::path::Path;
+
+/// The measures of a generation of a world.
+///
+/// The bounding box and the centroid are the ones of the cells which
+/// aren't **Dead**, and are `None` when every cell is **Dead**.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Stats {
+    /// The number of generations run since the first one.
+    pub generation: u64,
+
+    /// The number of **Alive** cells.
+    pub alive: usize,
+
+    /// The number of **Dying** cells, at any stage.
+    pub dying: usize,
+
+    /// The number of **Dead** cells.
+    pub dead: usize,
+
+    /// The number of cells which were **Dead** and are now **Alive**.
+    pub births: usize,
+
+    /// The number of cells whose state changed.
+    pub activity: usize,
+
+    /// The leftmost column of the cells.
+    pub min_x: Option<usize>,
+
+    /// The topmost row of the cells.
+    pub min_y: Option<usize>,
+
+    /// The rightmost column of the cells.
+    pub max_x: Option<usize>,
+
+    /// The bottommost row of the cells.
+    pub max_y: Option<usize>,
+
+    /// The mean column of the cells.
+    pub centroid_x: Option<f64>,
+
+    /// The mean row of the cells.
+    pub centroid_y: Option<f64>,
+}
+
+impl Stats {
+    /// Return the measures of `ws` at `generation`, the births and the
+    /// activity being counted from `previous`, its preceding generation.
+    /// Without a preceding generation, they are 0.
+    ///
+    /// Both worlds must have the same dimensions.
+    pub fn of(ws: &WorldState, previous: Option<&WorldState>, generation: u64) -> Stats {
+        let mut stats = Stats {
+            generation,
+            alive: 0,
+            dying: 0,
+            dead: 0,
+            births: 0,
+            activity: 0,
+            min_x: None,
+            min_y: None,
+            max_x: None,
+            max_y: None,
+            centroid_x: None,
+            centroid_y: None,
+        };
+        let (mut sum_x, mut sum_y) = (0, 0);
+        for (x, y, cell) in ws.iter() {
+            match cell {
+                CellState::Alive => stats.alive += 1,
+                CellState::Dying(_) => stats.dying += 1,
+                CellState::Dead => {
+                    stats.dead += 1;
+                    continue;
+                }
+            }
+            stats.min_x = Some(stats.min_x.map_or(x, |m| m.min(x)));
+            stats.min_y = Some(stats.min_y.map_or(y, |m| m.min(y)));
+            stats.max_x = Some(stats.max_x.map_or(x, |m| m.max(x)));
+            stats.max_y = Some(stats.max_y.map_or(y, |m| m.max(y)));
+            sum_x += x;
+            sum_y += y;
+        }
+        let cells = stats.alive + stats.dying;
+        if cells > 0 {
+            stats.centroid_x = Some(sum_x as f64 / cells as f64);
+            stats.centroid_y = Some(sum_y as f64 / cells as f64);
+        }
+        if let Some(previous) = previous {
+            for ((_, _, before), (_, _, after)) in previous.iter().zip(ws.iter()) {
+                if before != after {
+                    stats.activity += 1;
+                    if before == CellState::Dead && after == CellState::Alive {
+                        stats.births += 1;
+                    }
+                }
+            }
+        }
+        stats
+    }
+}
+
+/// The formats in which the **Stats** of the generations can be written,
+/// one line per generation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum StatsFormat {
+    /// Comma-separated values, after a line naming the columns. The
+    /// measures which are `None` are left empty.
+    Csv,
+
+    /// A JSON object per line, the measures which are `None` being `null`.
+    JsonLines,
+}
+
+impl StatsFormat {
+    /// Return the format of the file at `path`, guessed from its extension.
+    /// Files with an unknown extension are considered to be CSV files.
+    pub fn of(path: &Path) -> StatsFormat {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("jsonl" | "json") => StatsFormat::JsonLines,
+            _ => StatsFormat::Csv,
+        }
+    }
+
+    /// Return the line written before the first generation, if any.
+    pub fn header(self) -> Option<&'static str> {
+        match self {
+            StatsFormat::Csv => Some(
+                "generation,alive,dying,dead,births,activity,\
+                 min_x,min_y,max_x,max_y,centroid_x,centroid_y",
+            ),
+            StatsFormat::JsonLines => None,
+        }
+    }
+
+    /// Write the measures of a generation as a line, without its end.
+    pub fn write(self, stats: &Stats) -> String {
+        match self {
+            StatsFormat::Csv => {
+                fn field<T: ToString>(value: Option<T>) -> String {
+                    value.map(|v| v.to_string()).unwrap_or_default()
+                }
+                format!(
+                    "{},{},{},{},{},{},{},{},{},{},{},{}",
+                    stats.generation,
+                    stats.alive,
+                    stats.dying,
+                    stats.dead,
+                    stats.births,
+                    stats.activity,
+                    field(stats.min_x),
+                    field(stats.min_y),
+                    field(stats.max_x),
+                    field(stats.max_y),
+                    field(stats.centroid_x),
+                    field(stats.centroid_y),
+                )
+            }
+            StatsFormat::JsonLines => {
+                serde_json::to_string(stats).expect("the stats are valid JSON")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::world_state::Spawn;
+
+    #[test]
+    fn test_counts() {
+        let mut ws = WorldState::new(4);
+        ws.spawn_osc3(0, 0).unwrap();
+        let stats = Stats::of(&ws, None, 0);
+        assert_eq!((stats.alive, stats.dying, stats.dead), (4, 4, 8));
+        assert_eq!((stats.births, stats.activity), (0, 0));
+        assert_eq!(
+            (stats.min_x, stats.min_y, stats.max_x, stats.max_y),
+            (Some(0), Some(0), Some(3), Some(3))
+        );
+        assert_eq!((stats.centroid_x, stats.centroid_y), (Some(1.5), Some(1.5)));
+    }
+
+    #[test]
+    fn test_births_and_activity() {
+        let mut ws = WorldState::new(4);
+        ws.set(1, 1, CellState::Alive).unwrap();
+        ws.set(2, 1, CellState::Alive).unwrap();
+        let previous = ws.clone();
+        ws.next();
+        // the 2 cells die, and the 4 cells above and below them are born.
+        let stats = Stats::of(&ws, Some(&previous), 1);
+        assert_eq!((stats.alive, stats.dying), (4, 2));
+        assert_eq!((stats.births, stats.activity), (4, 6));
+    }
+
+    #[test]
+    fn test_empty_world() {
+        let stats = Stats::of(&WorldState::with_dimensions(3, 2), None, 7);
+        assert_eq!(stats.dead, 6);
+        assert_eq!(stats.min_x, None);
+        assert_eq!(stats.centroid_y, None);
+        assert_eq!(StatsFormat::Csv.write(&stats), "7,0,0,6,0,0,,,,,,");
+        assert!(StatsFormat::JsonLines
+            .write(&stats)
+            .contains(r#""min_x":null"#));
+    }
+
+    #[test]
+    fn test_formats() {
+        let mut ws = WorldState::new(3);
+        ws.set(1, 2, CellState::Alive).unwrap();
+        let stats = Stats::of(&ws, None, 0);
+        let csv = StatsFormat::Csv;
+        assert_eq!(
+            csv.header().unwrap().split(',').count(),
+            csv.write(&stats).split(',').count()
+        );
+        assert_eq!(csv.write(&stats), "0,1,0,8,0,0,1,2,1,2,1,2");
+        assert_eq!(
+            StatsFormat::JsonLines.write(&stats),
+            r#"{"generation":0,"alive":1,"dying":0,"dead":8,"births":0,"activity":0,"min_x":1,"min_y":2,"max_x":1,"max_y":2,"centroid_x":1.0,"centroid_y":2.0}"#
+        );
+        assert_eq!(
+            StatsFormat::of(Path::new("run.jsonl")),
+            StatsFormat::JsonLines
+        );
+        assert_eq!(StatsFormat::of(Path::new("run.csv")), StatsFormat::Csv);
+    }
+}
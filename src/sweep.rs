@@ -0,0 +1,574 @@
+use crate::world_state::cycle::Outcome;
+use crate::world_state::{CellState, ConfigError, WorldState};
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use std::thread;
+
+/// The values taken by a parameter of a **Sweep**, written as values and
+/// ranges separated by commas (e.g. `10,20..50:10`). A range is written
+/// `START..END`, the end being included, optionally followed by its step
+/// (e.g. `0.1..0.5:0.1`), which is 1 by default for whole numbers.
+///
+/// A span has at most `MAX_VALUES` values.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Span<T> {
+    /// The values, in the order they were written.
+    pub values: Vec<T>,
+}
+
+/// The number of values a **Span** can have.
+pub const MAX_VALUES: usize = 10_000;
+
+/// The number of worlds a **Sweep** can run.
+pub const MAX_RUNS: usize = 1_000_000;
+
+/// A kind of value which can be swept over.
+pub trait Step: FromStr + Copy + PartialOrd + Default {
+    /// The step of a range written without one, if there is one.
+    const STEP: Option<Self>;
+
+    /// Return the number of values from `start` to `end` included, `step`
+    /// apart, or `None` if it doesn't fit in a `usize`.
+    fn count(start: Self, end: Self, step: Self) -> Option<usize>;
+
+    /// Return the values from `start` to `end` included, `step` apart.
+    fn range(start: Self, end: Self, step: Self) -> Vec<Self>;
+}
+
+impl Step for usize {
+    const STEP: Option<usize> = Some(1);
+
+    fn count(start: usize, end: usize, step: usize) -> Option<usize> {
+        ((end - start) / step).checked_add(1)
+    }
+
+    fn range(start: usize, end: usize, step: usize) -> Vec<usize> {
+        (start..=end).step_by(step).collect()
+    }
+}
+
+impl Step for u64 {
+    const STEP: Option<u64> = Some(1);
+
+    fn count(start: u64, end: u64, step: u64) -> Option<usize> {
+        usize::try_from((end - start) / step).ok()?.checked_add(1)
+    }
+
+    fn range(start: u64, end: u64, step: u64) -> Vec<u64> {
+        (start..=end).step_by(step as usize).collect()
+    }
+}
+
+impl Step for f64 {
+    const STEP: Option<f64> = None;
+
+    /// `end` is counted despite the rounding errors.
+    fn count(start: f64, end: f64, step: f64) -> Option<usize> {
+        let steps = ((end - start) / step + 1e-9).floor();
+        // also `None` for an infinite or a NaN number of steps.
+        if (0.0..usize::MAX as f64).contains(&steps) {
+            (steps as usize).checked_add(1)
+        } else {
+            None
+        }
+    }
+
+    /// The values are computed from `start`, so that the rounding errors
+    /// don't add up.
+    fn range(start: f64, end: f64, step: f64) -> Vec<f64> {
+        let count = f64::count(start, end, step).unwrap_or(0);
+        (0..count).map(|i| start + step * i as f64).collect()
+    }
+}
+
+/// The reasons why a **Span** can't be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseSpanError {
+    /// A value isn't valid.
+    Value(String),
+
+    /// A range has no value, its step isn't valid, or it has too many
+    /// values.
+    Range(String),
+}
+
+impl fmt::Display for ParseSpanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseSpanError::Value(value) => write!(f, "`{value}` isn't a valid value"),
+            ParseSpanError::Range(range) => write!(
+                f,
+                "`{range}` isn't a range written `START..END:STEP`, \
+                 with START <= END, a positive STEP and at most {MAX_VALUES} values"
+            ),
+        }
+    }
+}
+
+impl Error for ParseSpanError {}
+
+impl<T: Step> FromStr for Span<T> {
+    type Err = ParseSpanError;
+
+    fn from_str(s: &str) -> Result<Span<T>, ParseSpanError> {
+        let value = |v: &str| {
+            v.trim()
+                .parse()
+                .map_err(|_| ParseSpanError::Value(v.trim().to_string()))
+        };
+        let mut values = vec![];
+        for part in s.split(',') {
+            let Some((start, end)) = part.split_once("..") else {
+                values.push(value(part)?);
+                continue;
+            };
+            let error = || ParseSpanError::Range(part.trim().to_string());
+            let (end, step) = match end.split_once(':') {
+                Some((end, step)) => (end, value(step)?),
+                None => (end, T::STEP.ok_or_else(error)?),
+            };
+            let (start, end) = (value(start)?, value(end)?);
+            if start > end || step <= T::default() {
+                return Err(error());
+            }
+            match T::count(start, end, step) {
+                Some(count) if count <= MAX_VALUES.saturating_sub(values.len()) => {}
+                _ => return Err(error()),
+            }
+            values.extend(T::range(start, end, step));
+        }
+        Ok(Span { values })
+    }
+}
+
+/// Square worlds filled at random with every combination of size,
+/// proportion of **Alive** cells and seed, to be run until they settle.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sweep {
+    /// The sizes of the worlds.
+    pub sizes: Span<usize>,
+
+    /// The proportions of **Alive** cells.
+    pub randomness: Span<f64>,
+
+    /// The seeds from which the cells are chosen.
+    pub seeds: Span<u64>,
+
+    /// The number of generations after which a world which hasn't
+    /// settled is given up on.
+    pub max_generations: u64,
+}
+
+/// How a world of a **Sweep** ended.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Run {
+    /// The size of the world.
+    pub size: usize,
+
+    /// The proportion of **Alive** cells at the beginning.
+    pub randomness: f64,
+
+    /// The seed from which the cells were chosen.
+    pub seed: u64,
+
+    /// How the world ended, or `None` if it didn't settle.
+    pub outcome: Option<Outcome>,
+
+    /// The generation at which the world died or its cycle started, or
+    /// the maximum number of generations if it didn't settle.
+    pub lifetime: u64,
+
+    /// The number of cells which aren't **Dead** at the end of the
+    /// lifetime.
+    pub population: usize,
+}
+
+impl Run {
+    /// Return how the run ended, as counted in a **Summary**.
+    pub fn ending(&self) -> Ending {
+        match self.outcome {
+            Some(Outcome::Extinct { .. }) => Ending::Extinct,
+            Some(Outcome::Cycle { period, .. }) => Ending::Period(period),
+            None => Ending::Unsettled,
+        }
+    }
+}
+
+/// The ways a world can end.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Ending {
+    /// Every cell died.
+    Extinct,
+
+    /// The cells repeat themselves with this period.
+    Period(u64),
+
+    /// The cells didn't settle in time.
+    Unsettled,
+}
+
+impl fmt::Display for Ending {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ending::Extinct => write!(f, "extinct"),
+            Ending::Period(period) => write!(f, "p{period}"),
+            Ending::Unsettled => write!(f, "unsettled"),
+        }
+    }
+}
+
+impl Sweep {
+    /// Run the worlds of the sweep, with up to `threads` worlds at the same
+    /// time, and return how they ended, by size, then proportion, then seed.
+    ///
+    /// An error is returned, before running any world, if a size or a
+    /// proportion isn't valid, or if there are more than `MAX_RUNS` worlds.
+    pub fn run(&self, threads: usize) -> Result<Vec<Run>, ConfigError> {
+        let (sizes, randomness, seeds) = (
+            self.sizes.values.len(),
+            self.randomness.values.len(),
+            self.seeds.values.len(),
+        );
+        match sizes
+            .checked_mul(randomness)
+            .and_then(|runs| runs.checked_mul(seeds))
+        {
+            Some(runs) if runs <= MAX_RUNS => {}
+            _ => {
+                return Err(ConfigError::Runs {
+                    sizes,
+                    randomness,
+                    seeds,
+                })
+            }
+        }
+        for &size in &self.sizes.values {
+            WorldState::try_with_dimensions(size, size)?;
+        }
+        for &randomness in &self.randomness.values {
+            if !(0.0..=1.0).contains(&randomness) {
+                return Err(ConfigError::Rate(randomness));
+            }
+        }
+
+        let mut runs = Vec::with_capacity(sizes * randomness * seeds);
+        for &size in &self.sizes.values {
+            for &randomness in &self.randomness.values {
+                for &seed in &self.seeds.values {
+                    runs.push(Run {
+                        size,
+                        randomness,
+                        seed,
+                        outcome: None,
+                        lifetime: 0,
+                        population: 0,
+                    });
+                }
+            }
+        }
+
+        let chunk = runs.len().div_ceil(threads.max(1)).max(1);
+        thread::scope(|scope| {
+            for chunk in runs.chunks_mut(chunk) {
+                scope.spawn(move || {
+                    for run in chunk {
+                        self.finish(run);
+                    }
+                });
+            }
+        });
+        Ok(runs)
+    }
+
+    /// Run the world of `run` until it settles, and fill in how it ended.
+    fn finish(&self, run: &mut Run) {
+        let mut ws = WorldState::new(run.size);
+        ws.randomize_with_seed(run.randomness, run.seed)
+            .expect("the proportion is checked");
+        run.outcome = ws.settle(self.max_generations);
+        run.lifetime = match run.outcome {
+            Some(Outcome::Extinct { generation }) => generation,
+            Some(Outcome::Cycle { transient, .. }) => transient,
+            None => self.max_generations,
+        };
+        run.population = ws.iter().filter(|&(_, _, c)| c != CellState::Dead).count();
+    }
+}
+
+/// The runs of a **Sweep** with the same size and proportion of **Alive**
+/// cells, aggregated.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Summary {
+    /// The size of the worlds.
+    pub size: usize,
+
+    /// The proportion of **Alive** cells at the beginning.
+    pub randomness: f64,
+
+    /// The number of runs.
+    pub runs: usize,
+
+    /// The mean of the lifetimes.
+    pub mean_lifetime: f64,
+
+    /// The median of the lifetimes.
+    pub median_lifetime: f64,
+
+    /// The mean of the final populations.
+    pub mean_population: f64,
+
+    /// The number of runs for each ending.
+    pub endings: BTreeMap<Ending, usize>,
+}
+
+/// The summaries of the runs of a **Sweep**.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Report {
+    /// The summaries, in the order of the runs.
+    pub summaries: Vec<Summary>,
+}
+
+impl Report {
+    /// Aggregate the `runs` with the same size and proportion of **Alive**
+    /// cells.
+    pub fn of(runs: &[Run]) -> Report {
+        let mut groups: Vec<Vec<&Run>> = vec![];
+        for run in runs {
+            match groups
+                .iter_mut()
+                .find(|g| (g[0].size, g[0].randomness) == (run.size, run.randomness))
+            {
+                Some(group) => group.push(run),
+                None => groups.push(vec![run]),
+            }
+        }
+        let summaries = groups
+            .into_iter()
+            .map(|group| {
+                let count = group.len() as f64;
+                let mut lifetimes: Vec<u64> = group.iter().map(|r| r.lifetime).collect();
+                lifetimes.sort_unstable();
+                let middle = lifetimes.len() / 2;
+                let median_lifetime = match lifetimes.len() % 2 {
+                    0 => (lifetimes[middle - 1] + lifetimes[middle]) as f64 / 2.0,
+                    _ => lifetimes[middle] as f64,
+                };
+                let mut endings = BTreeMap::new();
+                for run in &group {
+                    *endings.entry(run.ending()).or_default() += 1;
+                }
+                Summary {
+                    size: group[0].size,
+                    randomness: group[0].randomness,
+                    runs: group.len(),
+                    mean_lifetime: lifetimes.iter().sum::<u64>() as f64 / count,
+                    median_lifetime,
+                    mean_population: group.iter().map(|r| r.population).sum::<usize>() as f64
+                        / count,
+                    endings,
+                }
+            })
+            .collect();
+        Report { summaries }
+    }
+}
+
+impl fmt::Display for Report {
+    /// Write the report as a table, with a line for each summary.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>6}  {:>10}  {:>5}  {:>13}  {:>15}  {:>15}  endings",
+            "size", "randomness", "runs", "mean lifetime", "median lifetime", "mean population"
+        )?;
+        for summary in &self.summaries {
+            let endings: Vec<String> = summary
+                .endings
+                .iter()
+                .map(|(ending, count)| format!("{ending}:{count}"))
+                .collect();
+            writeln!(
+                f,
+                "{:>6}  {:>10.3}  {:>5}  {:>13.1}  {:>15.1}  {:>15.1}  {}",
+                summary.size,
+                summary.randomness,
+                summary.runs,
+                summary.mean_lifetime,
+                summary.median_lifetime,
+                summary.mean_population,
+                endings.join(" ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse_span() {
+        assert_eq!(
+            "7".parse(),
+            Ok(Span {
+                values: vec![7usize]
+            })
+        );
+        assert_eq!(
+            "1..3,10".parse(),
+            Ok(Span {
+                values: vec![1u64, 2, 3, 10]
+            })
+        );
+        assert_eq!(
+            "10..50:20".parse(),
+            Ok(Span {
+                values: vec![10usize, 30, 50]
+            })
+        );
+        let span: Span<f64> = "0.1..0.5:0.1".parse().unwrap();
+        assert_eq!(span.values.len(), 5);
+        assert!((span.values[4] - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse_malformed_span() {
+        assert_eq!(
+            "x".parse::<Span<usize>>(),
+            Err(ParseSpanError::Value("x".to_string()))
+        );
+        assert_eq!(
+            "5..1".parse::<Span<usize>>(),
+            Err(ParseSpanError::Range("5..1".to_string()))
+        );
+        assert_eq!(
+            "1..5:0".parse::<Span<usize>>(),
+            Err(ParseSpanError::Range("1..5:0".to_string()))
+        );
+        // a range of proportions needs a step.
+        assert_eq!(
+            "0.1..0.5".parse::<Span<f64>>(),
+            Err(ParseSpanError::Range("0.1..0.5".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_span_with_too_many_values() {
+        let span: Span<u64> = "1..9999,0".parse().unwrap();
+        assert_eq!(span.values.len(), MAX_VALUES);
+        assert_eq!(
+            "0,1..10000".parse::<Span<u64>>(),
+            Err(ParseSpanError::Range("1..10000".to_string()))
+        );
+        assert_eq!(
+            "0..18446744073709551615".parse::<Span<u64>>(),
+            Err(ParseSpanError::Range("0..18446744073709551615".to_string()))
+        );
+        assert_eq!(
+            "0..1:1e-300".parse::<Span<f64>>(),
+            Err(ParseSpanError::Range("0..1:1e-300".to_string()))
+        );
+        assert_eq!(
+            "0..inf:1".parse::<Span<f64>>(),
+            Err(ParseSpanError::Range("0..inf:1".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_run() {
+        let sweep = Sweep {
+            sizes: "8,12".parse().unwrap(),
+            randomness: "0,0.3".parse().unwrap(),
+            seeds: "1..3".parse().unwrap(),
+            max_generations: 200,
+        };
+        let runs = sweep.run(3).unwrap();
+        assert_eq!(runs.len(), 12);
+        assert_eq!(runs, sweep.run(1).unwrap());
+        assert_eq!(
+            (runs[3].size, runs[3].randomness, runs[3].seed),
+            (8, 0.3, 1)
+        );
+
+        // the empty worlds are extinct from the start.
+        assert_eq!(runs[0].outcome, Some(Outcome::Extinct { generation: 0 }));
+        assert_eq!((runs[0].lifetime, runs[0].population), (0, 0));
+
+        let report = Report::of(&runs);
+        assert_eq!(report.summaries.len(), 4);
+        assert_eq!(report.summaries[0].runs, 3);
+        assert_eq!(report.summaries[0].endings[&Ending::Extinct], 3);
+        assert_eq!(report.to_string().lines().count(), 5);
+    }
+
+    #[test]
+    fn test_invalid_sweep() {
+        let sweep = Sweep {
+            sizes: "8".parse().unwrap(),
+            randomness: "1.5".parse().unwrap(),
+            seeds: "1".parse().unwrap(),
+            max_generations: 10,
+        };
+        assert_eq!(sweep.run(1), Err(ConfigError::Rate(1.5)));
+    }
+
+    #[test]
+    fn test_too_many_runs() {
+        let sweep = Sweep {
+            sizes: "8..17".parse().unwrap(),
+            randomness: "0.0..0.999:0.001".parse().unwrap(),
+            seeds: "0..100".parse().unwrap(),
+            max_generations: 10,
+        };
+        assert_eq!(
+            sweep.run(1),
+            Err(ConfigError::Runs {
+                sizes: 10,
+                randomness: 1000,
+                seeds: 101
+            })
+        );
+    }
+
+    #[test]
+    fn test_report() {
+        let run = |lifetime, population, outcome| Run {
+            size: 10,
+            randomness: 0.5,
+            seed: 0,
+            outcome,
+            lifetime,
+            population,
+        };
+        let cycle = |period| {
+            Some(Outcome::Cycle {
+                transient: 0,
+                period,
+                dx: 0,
+                dy: 0,
+            })
+        };
+        let report = Report::of(&[
+            run(10, 0, Some(Outcome::Extinct { generation: 10 })),
+            run(30, 8, cycle(3)),
+            run(20, 4, cycle(3)),
+            run(100, 12, None),
+        ]);
+        let summary = &report.summaries[0];
+        assert_eq!(summary.mean_lifetime, 40.0);
+        assert_eq!(summary.median_lifetime, 25.0);
+        assert_eq!(summary.mean_population, 6.0);
+        assert_eq!(
+            report.to_string().lines().nth(1),
+            Some(
+                "    10       0.500      4           40.0             25.0              6.0  \
+                 extinct:1 p3:2 unsettled:1"
+            )
+        );
+    }
+}
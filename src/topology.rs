@@ -0,0 +1,78 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+
+/// The way the edges of the world are connected to each other.
+///
+/// The topology decides which cell is the neighbour of a cell lying on
+/// the border of the world.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Topology {
+    /// Everything outside the world is dead: corner cells have 3
+    /// neighbours and edge cells have 5.
+    #[default]
+    Bounded,
+
+    /// The left edge is glued to the right edge and the top edge to
+    /// the bottom edge, so cells leaving one side come back on the other.
+    Torus,
+
+    /// Like a torus, but crossing the top or bottom edge mirrors the
+    /// horizontal position.
+    KleinBottle,
+
+    /// Crossing the top or bottom edge mirrors the horizontal position,
+    /// and crossing the left or right edge mirrors the vertical position.
+    /// Corner cells see themselves across their corner.
+    ProjectivePlane,
+
+    /// The edges behave as mirrors: the cells outside the world copy
+    /// the cells of the border they face.
+    Reflective,
+}
+
+impl Topology {
+    /// Map the coordinates (`x`, `y`), possibly outside a world of
+    /// `width` x `height` cells, to the cell they designate.
+    ///
+    /// `None` is returned if the coordinates don't designate any cell.
+    pub fn wrap(self, x: isize, y: isize, width: usize, height: usize) -> Option<(usize, usize)> {
+        let (w, h) = (width as isize, height as isize);
+        if (0..w).contains(&x) && (0..h).contains(&y) {
+            return Some((x as usize, y as usize));
+        }
+
+        match self {
+            Topology::Bounded => None,
+            Topology::Torus => Some((x.rem_euclid(w) as usize, y.rem_euclid(h) as usize)),
+            Topology::KleinBottle => {
+                let mut nx = x.rem_euclid(w);
+                if y.div_euclid(h) % 2 != 0 {
+                    nx = w - 1 - nx;
+                }
+                Some((nx as usize, y.rem_euclid(h) as usize))
+            }
+            Topology::ProjectivePlane => {
+                let (mut nx, mut ny) = (x.rem_euclid(w), y.rem_euclid(h));
+                if y.div_euclid(h) % 2 != 0 {
+                    nx = w - 1 - nx;
+                }
+                if x.div_euclid(w) % 2 != 0 {
+                    ny = h - 1 - ny;
+                }
+                Some((nx as usize, ny as usize))
+            }
+            Topology::Reflective => Some((reflect(x, w) as usize, reflect(y, h) as usize)),
+        }
+    }
+}
+
+/// Fold `n` back into `0..len` as if both ends of the range were mirrors.
+fn reflect(n: isize, len: isize) -> isize {
+    let m = n.rem_euclid(2 * len);
+    if m < len {
+        m
+    } else {
+        2 * len - 1 - m
+    }
+}
@@ -0,0 +1,12 @@
+/// A corner of a triangle drawn on the screen.
+// use repr(C) to prevent rust to mess with the data.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vertex {
+    /// The position, from (-1, -1) at the top left of the window
+    /// to (1, 1) at its bottom right.
+    pub position: [f32; 2],
+
+    /// The RGB values of the color.
+    pub color: [f32; 3],
+}
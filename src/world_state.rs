@@ -1,69 +1,244 @@
-use crate::graphics::vulkan::Vertex;
+use self::bit_world::BitWorld;
+use crate::neighbourhood::Neighbourhood;
+use crate::pattern::Pattern;
+use crate::rule::Rule;
+use crate::scenario::Scenario;
+use crate::sweep;
+use crate::topology::Topology;
+use crate::vertex::Vertex;
 
 use rand::prelude::SliceRandom;
-use rand::thread_rng;
+use rand::{thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
+use std::error::Error;
 use std::fmt;
+use std::str::FromStr;
+use std::thread;
+
+/// Module containing a bit-packed version of the world, used
+/// to advance it quickly.
+pub mod bit_world;
+
+/// Module containing the detection of the cycles of a world,
+/// in which its cells repeat themselves.
+pub mod cycle;
+
+/// Module containing two copies of a world run side by side,
+/// one cell apart, to measure how the difference spreads.
+pub mod damage;
+
+/// Module containing the differences between two worlds,
+/// which can be printed and replayed.
+pub mod diff;
+
+/// Module containing a memoised version of the world, used
+/// to jump over a huge number of generations.
+pub mod hash_life;
+
+/// Module containing an unbounded version of the world, which
+/// only stores the cells that aren't dead.
+pub mod sparse_world;
 
 /// The color used to represent on a GUI the cells alive.
 /// The content is an array representing the RGB values.
 const ALIVE_COLOR: [f32; 3] = [1.0, 0.0, 0.0];
 
-/// The color used to represent on a GUI the cells dying.
+/// The color used to represent on a GUI the cells in the first stage
+/// of dying, the next stages fading towards black.
 /// The content is an array representing the RGB values.
 const DYING_COLOR: [f32; 3] = [0.5, 0.0, 0.0];
 
-/// The three states a cell can take.
-/// Each cell is considered to have 8 neighbors (Moore neighborhood).
-#[derive(Clone, Debug, PartialEq, Eq)]
-enum CellState {
-    
-    /// **Alive** cells become **Dying** on their next step,
-    /// regardless of there neighbours.
+/// The states a cell can take.
+/// The next state of a cell depends on the number of **Alive** cells in its
+/// neighbourhood, counted as the rule of the world says, the neighbourhood
+/// being the 8 cells around it (Moore neighbourhood) by default.
+///
+/// With the rule of Brian's Brain, there is a single stage of **Dying**.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CellState {
+    /// **Alive** cells stay **Alive** if the rule makes them survive with
+    /// their number of **Alive** neighbours, and start dying otherwise.
+    /// With the rule of Brian's Brain, they always become **Dying**.
     Alive,
-    
-    /// **Dying** cells become **Dead** on their next step,
-    /// regardless of there neighbours.
-    Dying,
-    
-    /// **Dead** cells can become **Alive** only if there are
-    /// exactly two cells **Alive** in their neighbourhood.
+
+    /// **Dying** cells go through the stages of decay of the rule,
+    /// starting at 0, then become **Dead**, regardless of their neighbours.
+    Dying(u8),
+
+    /// **Dead** cells become **Alive** if the rule makes them be born with
+    /// their number of **Alive** neighbours: exactly two with the rule of
+    /// Brian's Brain.
     Dead,
 }
 
 impl fmt::Display for CellState {
+    /// The first stage of **Dying** is shown as `X`, the next ones as
+    /// the lowercase letters (`a` for the second stage, and so on), and
+    /// the stages beyond `z` as `#`.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let c = match self {
             CellState::Alive => 'O',
             CellState::Dead => '.',
-            CellState::Dying => 'X',
+            CellState::Dying(0) => 'X',
+            CellState::Dying(stage @ 1..=26) => (b'a' + stage - 1) as char,
+            CellState::Dying(_) => '#',
         };
 
         write!(f, "{c}")
     }
 }
 
-/// This struct represents the entire Cellular Automaton.
+/// Read a cell shown by `CellState`'s `Display`, the stages of **Dying**
+/// shown as `#` being lost.
+impl TryFrom<char> for CellState {
+    type Error = ParseWorldError;
+
+    fn try_from(c: char) -> Result<CellState, ParseWorldError> {
+        match c {
+            'O' => Ok(CellState::Alive),
+            '.' => Ok(CellState::Dead),
+            'X' => Ok(CellState::Dying(0)),
+            'a'..='z' => Ok(CellState::Dying(c as u8 - b'a' + 1)),
+            _ => Err(ParseWorldError::Cell(c)),
+        }
+    }
+}
+
+/// The reasons why a world can't be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseWorldError {
+    /// A character doesn't show a cell.
+    Cell(char),
+
+    /// A row (counted from 0) doesn't have as many cells as the first one.
+    Ragged(usize),
+}
+
+impl fmt::Display for ParseWorldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseWorldError::Cell(c) => write!(f, "`{c}` doesn't show a cell"),
+            ParseWorldError::Ragged(row) => {
+                write!(
+                    f,
+                    "the row {row} doesn't have as many cells as the first one"
+                )
+            }
+        }
+    }
+}
+
+impl Error for ParseWorldError {}
+
+/// Marker used in a `NeighbourTable` for a neighbour lying outside the world.
+const NO_NEIGHBOUR: u32 = u32::MAX;
+
+/// The largest number of slots of a `NeighbourTable` (512 MiB), enough
+/// for a world of 4096x4096 cells with the neighbourhood of Brian's Brain.
+const MAX_TABLE_SLOTS: usize = 1 << 27;
+
+/// The neighbours of every cell, stored in a single flat vector.
+///
+/// Each cell owns `stride` consecutive slots, and the slots of the
+/// neighbours lying outside the world are filled with `NO_NEIGHBOUR`.
+/// Indexes are stored as `u32` to keep the table small for huge worlds.
+///
+/// When the table would have more than `MAX_TABLE_SLOTS` slots, it isn't
+/// filled, and the neighbours are found from the offsets instead.
 #[derive(Clone, Debug, PartialEq, Eq)]
+struct NeighbourTable {
+    stride: usize,
+    indexes: Option<Vec<u32>>,
+
+    /// The offsets of the neighbours of the cells of the even rows and of
+    /// the odd rows, and the world they are wrapped in.
+    offsets: [Vec<(isize, isize)>; 2],
+    width: usize,
+    height: usize,
+    topology: Topology,
+}
+
+impl NeighbourTable {
+    /// Return the indexes of the neighbours of the cell at index `i`.
+    fn get(&self, i: usize) -> impl Iterator<Item = usize> + '_ {
+        let stored = self.indexes.as_ref().map(|indexes| {
+            indexes[i * self.stride..(i + 1) * self.stride]
+                .iter()
+                .filter(|&&n| n != NO_NEIGHBOUR)
+                .map(|&n| n as usize)
+        });
+        let computed = match self.indexes {
+            Some(_) => None,
+            None => Some(self.compute(i)),
+        };
+        stored
+            .into_iter()
+            .flatten()
+            .chain(computed.into_iter().flatten())
+    }
+
+    /// Return the indexes of the neighbours of the cell at index `i`,
+    /// found from the offsets.
+    fn compute(&self, i: usize) -> impl Iterator<Item = usize> + '_ {
+        let (x, y) = (i % self.width, i / self.width);
+        self.offsets[y % 2].iter().filter_map(move |&(dx, dy)| {
+            let (nx, ny) = (x as isize + dx, y as isize + dy);
+            let (nx, ny) = self.topology.wrap(nx, ny, self.width, self.height)?;
+            Some(ny * self.width + nx)
+        })
+    }
+}
+
+/// This struct represents the entire Cellular Automaton.
+#[derive(Clone, Debug)]
 pub struct WorldState {
-    /// The size of the world representing the Cellular Automaton.
-    /// This value is *one side* of the world, and thus the *real* size
-    /// is this value squared (because the world is 2D).
-    size: u16,
+    /// The number of cells on each row of the world.
+    width: usize,
+
+    /// The number of rows of the world.
+    height: usize,
+
+    /// How the edges of the world are connected.
+    topology: Topology,
+
+    /// How the cells evolve from one generation to the next.
+    rule: Rule,
+
+    /// The cells counted as neighbours by the rule.
+    neighbourhood: Neighbourhood,
 
     /// The actual representation of the Cellular Automaton at a given time.
     /// It consists of a 1D vector of `CellState` values.
     world: Vec<CellState>,
 
-    neighbours: Vec<Vec<u16>>,
+    neighbours: NeighbourTable,
+
+    /// A bit-packed copy of the cells, through which a world following
+    /// Brian's Brain is advanced. It's made on the first generation, kept
+    /// in step when cells are set, and dropped when the world changes in
+    /// any other way.
+    bits: Option<Box<BitWorld>>,
 }
 
+/// The worlds are compared without their bit-packed copies, which only
+/// exist to advance them faster.
+impl PartialEq for WorldState {
+    fn eq(&self, other: &WorldState) -> bool {
+        (self.width, self.height, self.topology) == (other.width, other.height, other.topology)
+            && (&self.rule, &self.neighbourhood) == (&other.rule, &other.neighbourhood)
+            && self.world == other.world
+    }
+}
+
+impl Eq for WorldState {}
+
 impl fmt::Display for WorldState {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut s = String::from("");
+        let mut s = String::with_capacity(self.world.len() + self.height);
         for (i, item) in self.world.iter().enumerate() {
             s.push_str(&item.to_string());
-            if (i + 1) % self.size as usize == 0 {
+            if (i + 1) % self.width == 0 {
                 s.push('\n');
             }
         }
@@ -71,339 +246,803 @@ impl fmt::Display for WorldState {
     }
 }
 
+impl FromStr for WorldState {
+    type Err = ParseWorldError;
+
+    /// Parse a world shown by `WorldState`'s `Display`, one row per line.
+    /// The world follows the rule of Brian's Brain, in a bounded topology.
+    fn from_str(s: &str) -> Result<WorldState, ParseWorldError> {
+        let rows: Vec<&str> = s.lines().map(|row| row.trim_end_matches('\r')).collect();
+        let width = rows.first().map_or(0, |row| row.chars().count());
+
+        let mut ws = WorldState::with_dimensions(width, rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(ParseWorldError::Ragged(y));
+            }
+            for (x, c) in row.chars().enumerate() {
+                ws.world[y * width + x] = CellState::try_from(c)?;
+            }
+        }
+        Ok(ws)
+    }
+}
+
 impl WorldState {
     /// Create a new **WorldState** with a defined `size`.
     ///
     /// The `size` provided is used for the world's width and height.
-    pub fn new(size: u16) -> WorldState {
+    ///
+    /// # Panics
+    ///
+    /// Panics if the world has more cells than what a `u32` can index.
+    pub fn new(size: usize) -> WorldState {
+        Self::with_dimensions(size, size)
+    }
+
+    /// Create a new rectangular **WorldState** of `width` x `height` cells.
+    ///
+    /// The world is bounded by dead cells, see `WorldState::set_topology()`
+    /// to connect its edges instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the world has more cells than what a `u32` can index.
+    pub fn with_dimensions(width: usize, height: usize) -> WorldState {
+        let cells = width
+            .checked_mul(height)
+            .filter(|&cells| cells < NO_NEIGHBOUR as usize)
+            .expect("the world is too large to be indexed");
         WorldState {
-            size,
-            world: vec![CellState::Dead; size.pow(2).into()],
-            neighbours: Self::precompute_neighbours(size),
+            width,
+            height,
+            topology: Topology::Bounded,
+            rule: Rule::default(),
+            neighbourhood: Neighbourhood::default(),
+            world: vec![CellState::Dead; cells],
+            neighbours: Self::precompute_neighbours(
+                width,
+                height,
+                Topology::Bounded,
+                &Neighbourhood::default(),
+            ),
+            bits: None,
         }
     }
 
-    /// Compute every neighbours for each cell of the CA.
-    fn precompute_neighbours(size: u16) -> Vec<Vec<u16>> {
-        let mut neighbours: Vec<Vec<u16>> = vec![];
-        for i in 0..size.pow(2) {
-            let x = i % size;
-            let y = i / size;
-            // general case: 8 neighbours
-            if x > 0 && x < size - 1 && y > 0 && y < size - 1 {
-                neighbours.push(vec![
-                    (y - 1) * size + x - 1,
-                    (y - 1) * size + x,
-                    (y - 1) * size + x + 1,
-                    y * size + x - 1,
-                    y * size + x + 1,
-                    (y + 1) * size + x - 1,
-                    (y + 1) * size + x,
-                    (y + 1) * size + x + 1,
-                ]);
-            }
-            // top left corner: 3 neighbours
-            else if x == 0 && y == 0 {
-                neighbours.push(vec![1, size, size + 1])
-            }
-            // top right corner: 3 neighbours
-            else if x == size - 1 && y == 0 {
-                neighbours.push(vec![x - 1, size + x - 1, size + x]);
-            }
-            // bottom left corner: 3 neighbours
-            else if x == 0 && y == size - 1 {
-                neighbours.push(vec![(y - 1) * size, (y - 1) * size + 1, y * size + 1]);
-            }
-            // bottom right corner: 3 neighbours
-            else if x == size - 1 && y == size - 1 {
-                neighbours.push(vec![
-                    (y - 1) * size + x - 1,
-                    (y - 1) * size + x,
-                    y * size + x - 1,
-                ]);
-            }
-            // top edge: 5 neighbours
-            else if y == 0 {
-                neighbours.push(vec![x - 1, x + 1, size + x - 1, size + x, size + x + 1])
+    /// Create a new rectangular **WorldState** of `width` x `height` cells,
+    /// as `with_dimensions()` does, or return an error if the world has no
+    /// cell or is too large.
+    pub fn try_with_dimensions(width: usize, height: usize) -> Result<WorldState, ConfigError> {
+        match width.checked_mul(height) {
+            Some(0) => Err(ConfigError::Empty { width, height }),
+            Some(cells) if cells < NO_NEIGHBOUR as usize => {
+                Ok(Self::with_dimensions(width, height))
             }
-            // bottom edge: 5 neighbours
-            else if y == size - 1 {
-                neighbours.push(vec![
-                    (y - 1) * size + x - 1,
-                    (y - 1) * size + x,
-                    (y - 1) * size + x + 1,
-                    y * size + x - 1,
-                    y * size + x + 1,
-                ]);
-            }
-            //left edge: 5 neighbours
-            else if x == 0 && y < size {
-                neighbours.push(vec![
-                    (y - 1) * size,
-                    (y - 1) * size + 1,
-                    y * size + 1,
-                    (y + 1) * size,
-                    (y + 1) * size + 1,
-                ]);
-            }
-            // right edge: 5 neighbours
-            else if x == size - 1 {
-                neighbours.push(vec![
-                    (y - 1) * size + x - 1,
-                    (y - 1) * size + x,
-                    y * size + x - 1,
-                    (y + 1) * size + x - 1,
-                    (y + 1) * size + x,
-                ]);
-            }
-            // outside the range of the world: 0 neighbours
-            else {
-                neighbours.push(vec![]);
+            _ => Err(ConfigError::TooLarge { width, height }),
+        }
+    }
+
+    /// Return the number of cells on each row of the world.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Return the number of rows of the world.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Return the cells of the world, row by row.
+    pub fn rows(&self) -> impl Iterator<Item = &[CellState]> {
+        self.world.chunks(self.width.max(1))
+    }
+
+    /// Return the state of the cell at column `x` and row `y`, or an error
+    /// if the cell is outside the world.
+    pub fn get(&self, x: usize, y: usize) -> Result<CellState, OutOfBounds> {
+        self.index(x, y).map(|i| self.world[i])
+    }
+
+    /// Change the state of the cell at column `x` and row `y`, or return an
+    /// error if the cell is outside the world.
+    pub fn set(&mut self, x: usize, y: usize, state: CellState) -> Result<(), OutOfBounds> {
+        let i = self.index(x, y)?;
+        self.world[i] = state;
+        if let Some(bits) = &mut self.bits {
+            bits.set(x, y, state);
+        }
+        Ok(())
+    }
+
+    /// Return the index in `world` of the cell at column `x` and row `y`.
+    fn index(&self, x: usize, y: usize) -> Result<usize, OutOfBounds> {
+        if x < self.width && y < self.height {
+            Ok(y * self.width + x)
+        } else {
+            Err(OutOfBounds {
+                x: x as i64,
+                y: y as i64,
+            })
+        }
+    }
+
+    /// Return every cell of the world, row by row, as (`x`, `y`, `state`).
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, CellState)> + '_ {
+        self.world
+            .iter()
+            .enumerate()
+            .map(|(i, &cell)| (i % self.width, i / self.width, cell))
+    }
+
+    /// Return the number of cells of the world in the given state.
+    pub fn population(&self, state: CellState) -> usize {
+        self.world.iter().filter(|&&cell| cell == state).count()
+    }
+
+    /// Return how the edges of the world are connected.
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Change how the edges of the world are connected.
+    /// The cells of the world are left untouched.
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+        self.bits = None;
+        self.neighbours =
+            Self::precompute_neighbours(self.width, self.height, topology, &self.neighbourhood);
+    }
+
+    /// Return the cells counted as neighbours by the rule.
+    pub fn neighbourhood(&self) -> &Neighbourhood {
+        &self.neighbourhood
+    }
+
+    /// Change the cells counted as neighbours by the rule.
+    /// The cells of the world are left untouched.
+    pub fn set_neighbourhood(&mut self, neighbourhood: Neighbourhood) {
+        self.bits = None;
+        self.neighbours =
+            Self::precompute_neighbours(self.width, self.height, self.topology, &neighbourhood);
+        self.neighbourhood = neighbourhood;
+    }
+
+    /// Return how the cells evolve from one generation to the next.
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    /// Change how the cells evolve from one generation to the next.
+    /// The cells of the world are left untouched.
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+        self.bits = None;
+    }
+
+    /// Compute every neighbours for each cell of the CA.
+    ///
+    /// Depending on the `topology`, cells on the border of the world can
+    /// have fewer neighbours than the `neighbourhood` has, the missing ones
+    /// being marked with `NO_NEIGHBOUR` in the table.
+    ///
+    /// The table is left empty if it has more than `MAX_TABLE_SLOTS` slots,
+    /// the neighbours being found on the fly.
+    fn precompute_neighbours(
+        width: usize,
+        height: usize,
+        topology: Topology,
+        neighbourhood: &Neighbourhood,
+    ) -> NeighbourTable {
+        let mut table = NeighbourTable {
+            stride: neighbourhood.size(),
+            indexes: None,
+            offsets: [neighbourhood.offsets(false), neighbourhood.offsets(true)],
+            width,
+            height,
+            topology,
+        };
+        let slots = (width * height)
+            .checked_mul(table.stride)
+            .filter(|&slots| slots <= MAX_TABLE_SLOTS);
+        if let Some(slots) = slots {
+            let mut indexes = Vec::with_capacity(slots);
+            for y in 0..height {
+                for x in 0..width {
+                    for &(dx, dy) in &table.offsets[y % 2] {
+                        let (nx, ny) = (x as isize + dx, y as isize + dy);
+                        indexes.push(match topology.wrap(nx, ny, width, height) {
+                            Some((nx, ny)) => (ny * width + nx) as u32,
+                            None => NO_NEIGHBOUR,
+                        });
+                    }
+                }
             }
+            table.indexes = Some(indexes);
         }
-        neighbours
+        table
     }
 
-    /// Initialize the world with a certain amount of **CellState::On**.
+    /// Initialize the world with a certain amount of **CellState::Alive**,
+    /// chosen from a random seed which is returned, so that the world
+    /// can be made again with `randomize_with_seed()`.
     ///
     /// `on_rate` corresponds to the percentage of cells in the world to
-    /// set their state to **CellState::On**. `on_rate` is expected to be
-    /// between 0 and 1. Any value outside that range will cause a panic.
-    pub fn randomize(&mut self, on_rate: f64) {
+    /// set their state to **CellState::Alive**. `on_rate` is expected to be
+    /// between 0 and 1, an error being returned otherwise.
+    pub fn randomize(&mut self, on_rate: f64) -> Result<u64, ConfigError> {
+        let seed = thread_rng().gen();
+        self.randomize_with_seed(on_rate, seed)?;
+        Ok(seed)
+    }
+
+    /// Initialize the world with a certain amount of **CellState::Alive**,
+    /// as `randomize()` does, the cells being chosen from `seed`.
+    /// The same seed always gives the same world, whatever the platform.
+    pub fn randomize_with_seed(&mut self, on_rate: f64, seed: u64) -> Result<(), ConfigError> {
+        if !(0.0..=1.0).contains(&on_rate) {
+            return Err(ConfigError::Rate(on_rate));
+        }
+        self.bits = None;
         if on_rate == 1.0 {
             self.world = vec![CellState::Alive; self.world.len()];
-            return;
+            return Ok(());
         }
         let mut cell_indexes: Vec<_> = (0..self.world.len()).collect();
         let cell_amount = (on_rate * (self.world.len() as f64)) as usize;
 
-        cell_indexes.shuffle(&mut thread_rng());
+        cell_indexes.shuffle(&mut ChaCha8Rng::seed_from_u64(seed));
         for item in cell_indexes.iter_mut().take(cell_amount) {
-            self.world[*item as usize] = CellState::Alive;
+            self.world[*item] = CellState::Alive;
         }
+        Ok(())
     }
 
-    /// Advance the world to its next state.
+    /// Advance the world to its next state, following its rule.
+    ///
+    /// With the rule of Brian's Brain (the default one):
     /// A cell **Alive** is turned into **Dying**.
     /// A cell **Dying** is turned into **Dead**.
     /// A cell **Dead** is turned into **Alive** if two of its neighbours
     /// are also in **Alive** State.
+    ///
+    /// The worlds following the rule and the neighbourhood of Brian's Brain
+    /// are advanced through a **BitWorld** kept from one generation to the
+    /// next, much faster.
     pub fn next(&mut self) {
-        let (mut alive, mut dying, mut dead) = (vec![], vec![], vec![]);
-
-        for i in 0..self.world.len() {
-            match self.world[i] {
-                CellState::Alive => {
-                    dying.push(i);
-                }
-                CellState::Dead => {
-                    let alives = self.neighbours[i]
-                        .iter()
-                        .filter(|&n| self.world[*n as usize] == CellState::Alive)
-                        .count();
-
-                    if alives == 2 {
-                        alive.push(i);
-                    }
-                }
-                CellState::Dying => {
-                    dead.push(i);
-                }
-            }
+        if self.is_brian_s_brain() {
+            let mut bits = self
+                .bits
+                .take()
+                .unwrap_or_else(|| Box::new(BitWorld::from(&*self)));
+            bits.next();
+            bits.copy_changes(&mut self.world);
+            self.bits = Some(bits);
+        } else {
+            self.next_by_cell();
         }
+    }
 
-        // update the world
-        alive.iter().for_each(|&n| self.world[n] = CellState::Alive);
-        dying.iter().for_each(|&n| self.world[n] = CellState::Dying);
-        dead.iter().for_each(|&n| self.world[n] = CellState::Dead);
+    /// Advance the world to its next state like `WorldState::next()`, one
+    /// cell at a time, whatever its rule and neighbourhood.
+    pub(crate) fn next_by_cell(&mut self) {
+        self.world = (0..self.world.len()).map(|i| self.next_cell(i)).collect();
+        self.bits = None;
     }
 
-    /// Return vertices of the cells with `CellState::On` or `CellState::Dying`.
-    /// Moreover, each cell is represented by 6 vertices (2 triangles).
-    pub fn as_vertices(&self) -> Vec<Vertex> {
-        let mut updated_cells: Vec<Vertex> = vec![];
+    /// Return whether the world follows the rule and the neighbourhood of
+    /// Brian's Brain, the only ones a **BitWorld** computes.
+    fn is_brian_s_brain(&self) -> bool {
+        self.rule == Rule::default() && self.neighbourhood == Neighbourhood::default()
+    }
 
-        let cell_w = 2.0 / self.size as f32;
-        let cell_h = 2.0 / self.size as f32;
-        for (i, item) in self.world.iter().enumerate() {
-            let cell_x = (i % self.size as usize) as f32;
-            let cell_y = (i / self.size as usize) as f32;
-
-            // left triangle : ◺
-            let (x1, y1) = (-1.0 + cell_w * cell_x, -1.0 + cell_h * cell_y);
-            let (x2, y2) = (-1.0 + cell_w * cell_x, -1.0 + cell_h * (cell_y + 1.0));
-            let (x3, y3) = (
-                -1.0 + cell_w * (cell_x + 1.0),
-                -1.0 + cell_h * (cell_y + 1.0),
-            );
-            // right triangle : ◹
-            let (x4, y4) = (x1, y1);
-            let (x5, y5) = (-1.0 + cell_w * (cell_x + 1.0), -1.0 + cell_h * cell_y);
-            let (x6, y6) = (x3, y3);
-
-            match item {
-                CellState::Alive => {
-                    let mut cell_vertices = vec![
-                        Vertex {
-                            position: [x1, y1],
-                            color: ALIVE_COLOR,
-                        },
-                        Vertex {
-                            position: [x2, y2],
-                            color: ALIVE_COLOR,
-                        },
-                        Vertex {
-                            position: [x3, y3],
-                            color: ALIVE_COLOR,
-                        },
-                        Vertex {
-                            position: [x4, y4],
-                            color: ALIVE_COLOR,
-                        },
-                        Vertex {
-                            position: [x5, y5],
-                            color: ALIVE_COLOR,
-                        },
-                        Vertex {
-                            position: [x6, y6],
-                            color: ALIVE_COLOR,
-                        },
-                    ];
-                    updated_cells.append(&mut cell_vertices);
-                }
-                CellState::Dying => {
-                    let mut cell_vertices = vec![
-                        Vertex {
-                            position: [x1, y1],
-                            color: DYING_COLOR,
-                        },
-                        Vertex {
-                            position: [x2, y2],
-                            color: DYING_COLOR,
-                        },
-                        Vertex {
-                            position: [x3, y3],
-                            color: DYING_COLOR,
-                        },
-                        Vertex {
-                            position: [x4, y4],
-                            color: DYING_COLOR,
-                        },
-                        Vertex {
-                            position: [x5, y5],
-                            color: DYING_COLOR,
-                        },
-                        Vertex {
-                            position: [x6, y6],
-                            color: DYING_COLOR,
-                        },
-                    ];
-                    updated_cells.append(&mut cell_vertices);
-                }
-                CellState::Dead => {}
-            }
+    /// Return the state the cell at index `i` takes in the next generation,
+    /// following the same rules as `WorldState::next()`.
+    fn next_cell(&self, i: usize) -> CellState {
+        self.rule.next_state(self.world[i], || {
+            self.neighbours
+                .get(i)
+                .filter(|&n| self.world[n] == CellState::Alive)
+                .count()
+        })
+    }
+
+    /// Advance the world to its next state like `WorldState::next()`, but
+    /// split the rows into `threads` bands computed at the same time.
+    ///
+    /// Each band is written into the next generation, while the neighbours
+    /// of its cells are read from the current generation.
+    /// With a single thread, this is the same as `WorldState::next()`.
+    pub fn next_parallel(&mut self, threads: usize) {
+        if threads <= 1 {
+            return self.next();
         }
-        updated_cells
+        if self.is_brian_s_brain() {
+            let mut bits = self
+                .bits
+                .take()
+                .unwrap_or_else(|| Box::new(BitWorld::from(&*self)));
+            bits.next_parallel(threads);
+            bits.copy_changes(&mut self.world);
+            self.bits = Some(bits);
+            return;
+        }
+
+        let band_cells = (self.height.div_ceil(threads) * self.width).max(1);
+        let mut next_world = vec![CellState::Dead; self.world.len()];
+
+        thread::scope(|scope| {
+            for (b, band) in next_world.chunks_mut(band_cells).enumerate() {
+                let ws = &*self;
+                scope.spawn(move || {
+                    for (j, cell) in band.iter_mut().enumerate() {
+                        *cell = ws.next_cell(b * band_cells + j);
+                    }
+                });
+            }
+        });
+
+        self.world = next_world;
+    }
+
+    /// Return the world of the example with the number `n`, from 1 to 3,
+    /// or an error if there is none. The examples are the scenarios bundled
+    /// from the `scenarios` directory.
+    pub fn example(n: u16) -> Result<WorldState, ConfigError> {
+        let scenario = Scenario::example(n)?;
+        Ok(scenario
+            .world(0)
+            .expect("the bundled scenarios fit in their world"))
     }
 
     /// Initialize a world 14x14 with 5x3-period oscillators.
     /// Example made by **boreec**.
     pub fn example1() -> WorldState {
-        let mut ws = WorldState::new(14);
-        ws.spawn_osc3(0, 0);
-        ws.spawn_osc3(10, 10);
-        ws.spawn_osc3(0, 10);
-        ws.spawn_osc3(10, 0);
-        ws.spawn_osc3(5, 5);
-        ws
+        WorldState::example(1).unwrap()
     }
 
     /// Initialize a world 100x100 with many gliders creating
     /// a breeder. Example made by **Wojowu** on `conwaylife.com`.
     pub fn example2() -> WorldState {
-        let mut ws = WorldState::new(100);
-        ws.spawn_glider4_downward(42, 0);
-        ws.spawn_glider4_downward(30, 18);
-        ws.spawn_glider4_downward(30, 22);
-        ws.spawn_glider4_downward(13, 42);
-        ws.spawn_glider4_leftward(23, 57);
-        ws.spawn_glider4_leftward(19, 62);
-        ws.spawn_glider4_upward(10, 68);
-        ws.spawn_glider4_upward(24, 87);
-        ws.spawn_glider4_upward(28, 93);
-        ws
+        WorldState::example(2).unwrap()
     }
 
     /// Initialize a world 100x100 with a wick.
     /// Example made by **The Turtle** on `conwaylife.com`.
     pub fn example3() -> WorldState {
-        let mut ws = WorldState::new(100);
-        ws.spawn_wick3(50, 50);
-        ws
-    }
-    pub fn spawn_osc3(&mut self, x: usize, y: usize) {
-        let dying_cells = [
-            (x + 1, y + 1),
-            (x + 2, y + 1),
-            (x + 1, y + 2),
-            (x + 2, y + 2),
-        ];
-        let alive_cells = [(x, y + 1), (x + 2, y), (x + 1, y + 3), (x + 3, y + 2)];
-
-        for i in alive_cells {
-            self.world[i.0 * self.size as usize + i.1] = CellState::Alive;
-        }
-        for i in dying_cells {
-            self.world[i.0 * self.size as usize + i.1] = CellState::Dying;
-        }
+        WorldState::example(3).unwrap()
     }
+}
 
-    pub fn spawn_glider4_downward(&mut self, x: usize, y: usize) {
-        let dying_cells = [(x, y), (x + 1, y)];
-        let alive_cells = [(x, y + 1), (x + 1, y + 1)];
+impl Automaton for WorldState {
+    fn step(&mut self, threads: usize) {
+        self.next_parallel(threads);
+    }
 
-        for i in alive_cells {
-            self.world[i.1 * self.size as usize + i.0] = CellState::Alive;
-        }
-        for i in dying_cells {
-            self.world[i.1 * self.size as usize + i.0] = CellState::Dying;
+    fn as_vertices(&self) -> Vec<Vertex> {
+        let dying_stages = self.rule.dying_stages();
+        let cells = self
+            .iter()
+            .filter_map(|(x, y, state)| Some((x, y, cell_color(state, dying_stages)?)));
+        let shape = CellShape::of(&self.neighbourhood, 0);
+        cells_as_vertices(self.width, self.height, shape, cells)
+    }
+
+    fn is_extinct(&self) -> bool {
+        self.world.iter().all(|&cell| cell == CellState::Dead)
+    }
+}
+
+impl Spawn for WorldState {
+    fn contains(&self, x: i64, y: i64) -> bool {
+        (0..self.width as i64).contains(&x) && (0..self.height as i64).contains(&y)
+    }
+
+    fn spawn_cell(&mut self, x: i64, y: i64, state: CellState) -> Result<(), OutOfBounds> {
+        if self.contains(x, y) {
+            self.set(x as usize, y as usize, state)
+        } else {
+            Err(OutOfBounds { x, y })
         }
     }
+}
 
-    pub fn spawn_glider4_upward(&mut self, x: usize, y: usize) {
-        let alive_cells = [(x, y), (x + 1, y)];
-        let dying_cells = [(x, y + 1), (x + 1, y + 1)];
+/// The operations needed to run and display a world, whatever the way
+/// its cells are stored.
+pub trait Automaton: fmt::Display {
+    /// Advance the world to its next state, using up to `threads` threads.
+    fn step(&mut self, threads: usize);
+
+    /// Return vertices of the cells with `CellState::Alive` or `CellState::Dying`.
+    /// Moreover, each cell is represented by 6 vertices (2 triangles), or by
+    /// 12 vertices (4 triangles) when the cells are drawn as hexagons.
+    fn as_vertices(&self) -> Vec<Vertex>;
+
+    /// Return whether every cell is **Dead**, so that the world won't
+    /// change anymore, whatever part of it is displayed.
+    fn is_extinct(&self) -> bool;
+}
+
+/// The shape in which the cells are drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CellShape {
+    /// Squares, for the neighbourhoods of a square grid.
+    Square,
+
+    /// Hexagons, every other row being shifted by half a cell to the right
+    /// (the first row being shifted if `first_row_shifted` is true).
+    Hexagon { first_row_shifted: bool },
+}
 
-        for i in alive_cells {
-            self.world[i.1 * self.size as usize + i.0] = CellState::Alive;
+impl CellShape {
+    /// Return the shape of the cells of a world using `neighbourhood`,
+    /// whose first displayed row is the row `first_row`.
+    fn of(neighbourhood: &Neighbourhood, first_row: i64) -> CellShape {
+        match neighbourhood {
+            Neighbourhood::Hexagonal => CellShape::Hexagon {
+                first_row_shifted: first_row.rem_euclid(2) == 1,
+            },
+            _ => CellShape::Square,
         }
-        for i in dying_cells {
-            self.world[i.1 * self.size as usize + i.0] = CellState::Dying;
+    }
+}
+
+/// Return the color in which a cell in `state` is drawn, or `None` if it's
+/// **Dead** and isn't drawn. `dying_stages` is the number of stages of
+/// **Dying** of the rule.
+fn cell_color(state: CellState, dying_stages: u8) -> Option<[f32; 3]> {
+    match state {
+        CellState::Alive => Some(ALIVE_COLOR),
+        CellState::Dying(stage) => {
+            let fading = 1.0 - stage as f32 / dying_stages.max(1) as f32;
+            Some(DYING_COLOR.map(|c| c * fading))
         }
+        CellState::Dead => None,
     }
+}
 
-    pub fn spawn_glider4_leftward(&mut self, x: usize, y: usize) {
-        let dying_cells = [(x + 1, y), (x + 1, y + 1)];
-        let alive_cells = [(x, y), (x, y + 1)];
+/// Return the vertices of the `cells` of a rectangle of `width` x `height`
+/// cells, given as (`x`, `y`, `color`) with `x` and `y` in the rectangle.
+///
+/// Cells keep their proportions: a rectangular world is centered on the
+/// screen, leaving empty bands along its shortest dimension.
+fn cells_as_vertices(
+    width: usize,
+    height: usize,
+    shape: CellShape,
+    cells: impl Iterator<Item = (usize, usize, [f32; 3])>,
+) -> Vec<Vertex> {
+    let mut updated_cells: Vec<Vertex> = vec![];
 
-        for i in alive_cells {
-            self.world[i.1 * self.size as usize + i.0] = CellState::Alive;
-        }
-        for i in dying_cells {
-            self.world[i.1 * self.size as usize + i.0] = CellState::Dying;
+    // the size of the rectangle, in cell widths.
+    let (columns, rows) = match shape {
+        CellShape::Square => (width as f32, height as f32),
+        // pointy-top hexagons of width 1 have a side of 1 / sqrt(3), and
+        // their rows overlap on half a side.
+        CellShape::Hexagon { .. } => (
+            width as f32 + if height > 1 { 0.5 } else { 0.0 },
+            (1.5 * height as f32 + 0.5) / 3f32.sqrt(),
+        ),
+    };
+    let cell_w = 2.0 / columns.max(rows);
+    let cell_h = cell_w;
+    let origin_x = -cell_w * columns / 2.0;
+    let origin_y = -cell_h * rows / 2.0;
+    for (x, y, color) in cells {
+        let (cell_x, cell_y) = (x as f32, y as f32);
+
+        let positions = match shape {
+            CellShape::Square => {
+                // left triangle : ◺
+                let (x1, y1) = (origin_x + cell_w * cell_x, origin_y + cell_h * cell_y);
+                let (x2, y2) = (
+                    origin_x + cell_w * cell_x,
+                    origin_y + cell_h * (cell_y + 1.0),
+                );
+                let (x3, y3) = (
+                    origin_x + cell_w * (cell_x + 1.0),
+                    origin_y + cell_h * (cell_y + 1.0),
+                );
+                // right triangle : ◹
+                let (x4, y4) = (x1, y1);
+                let (x5, y5) = (
+                    origin_x + cell_w * (cell_x + 1.0),
+                    origin_y + cell_h * cell_y,
+                );
+                let (x6, y6) = (x3, y3);
+                vec![[x1, y1], [x2, y2], [x3, y3], [x4, y4], [x5, y5], [x6, y6]]
+            }
+            CellShape::Hexagon { first_row_shifted } => {
+                let side = cell_w / 3f32.sqrt();
+                let shift = if (y % 2 == 1) != first_row_shifted {
+                    0.5
+                } else {
+                    0.0
+                };
+                let center_x = origin_x + cell_w * (cell_x + 0.5 + shift);
+                let center_y = origin_y + side * (1.0 + 1.5 * cell_y);
+                let (left, right) = (center_x - cell_w / 2.0, center_x + cell_w / 2.0);
+                let (top, bottom) = (center_y - side, center_y + side);
+                let (upper, lower) = (center_y - side / 2.0, center_y + side / 2.0);
+
+                // top triangle, middle rectangle and bottom triangle : ⬡
+                vec![
+                    [left, upper],
+                    [center_x, top],
+                    [right, upper],
+                    [left, upper],
+                    [right, upper],
+                    [right, lower],
+                    [left, upper],
+                    [right, lower],
+                    [left, lower],
+                    [left, lower],
+                    [right, lower],
+                    [center_x, bottom],
+                ]
+            }
+        };
+        for position in positions {
+            updated_cells.push(Vertex { position, color });
         }
     }
+    updated_cells
+}
+
+/// The error returned when a cell is outside the world.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutOfBounds {
+    /// The column of the cell.
+    pub x: i64,
+
+    /// The row of the cell.
+    pub y: i64,
+}
+
+impl fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the cell ({}, {}) is outside the world", self.x, self.y)
+    }
+}
+
+impl Error for OutOfBounds {}
+
+/// The reasons why a world can't be made as asked.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConfigError {
+    /// The rate of **Alive** cells isn't between 0 and 1.
+    Rate(f64),
+
+    /// The world has no cell.
+    Empty {
+        /// The number of columns asked for.
+        width: usize,
+        /// The number of rows asked for.
+        height: usize,
+    },
+
+    /// The world has more cells than what a `u32` can index.
+    TooLarge {
+        /// The number of columns asked for.
+        width: usize,
+        /// The number of rows asked for.
+        height: usize,
+    },
+
+    /// There is no example with that number.
+    Example(u16),
+
+    /// The rule or the neighbourhood isn't the one of Brian's Brain,
+    /// which is the only one that can jump over generations.
+    Jump,
+
+    /// The jump goes beyond the generations a **HashLife** universe can
+    /// reach, `hash_life::MAX_JUMP`.
+    JumpLength(u64),
+
+    /// The topology connects the edges of the world, which a jump ignores.
+    JumpTopology(Topology),
+
+    /// A soup with the C4 or the D8 symmetry, which turns it by a quarter
+    /// of a turn, has an area which isn't a square.
+    Symmetry {
+        /// The number of columns of the area, in the world.
+        width: u64,
+        /// The number of rows of the area, in the world.
+        height: u64,
+    },
 
-    pub fn spawn_wick3(&mut self, x: usize, y: usize) {
-        let dying_cells = [(x + 1, y + 2)];
-        let alive_cells = [(x, y), (x + 1, y), (x + 2, y), (x, y + 1), (x + 2, y + 1)];
+    /// A sweep runs more worlds than `sweep::MAX_RUNS`.
+    Runs {
+        /// The number of sizes.
+        sizes: usize,
+        /// The number of proportions of **Alive** cells.
+        randomness: usize,
+        /// The number of seeds.
+        seeds: usize,
+    },
+}
 
-        for i in alive_cells {
-            self.world[i.1 * self.size as usize + i.0] = CellState::Alive;
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Rate(rate) => {
+                write!(f, "the rate of alive cells {rate} isn't between 0 and 1")
+            }
+            ConfigError::Empty { width, height } => {
+                write!(f, "a world of {width}x{height} cells has no cell")
+            }
+            ConfigError::TooLarge { width, height } => {
+                write!(f, "a world of {width}x{height} cells is too large")
+            }
+            ConfigError::Example(n) => {
+                write!(f, "there is no example {n}, the examples are 1, 2 and 3")
+            }
+            ConfigError::Jump => write!(
+                f,
+                "only the rule and the neighbourhood of Brian's Brain can jump over generations"
+            ),
+            ConfigError::JumpLength(generations) => write!(
+                f,
+                "a jump over {generations} generations goes beyond generation {}",
+                hash_life::MAX_JUMP
+            ),
+            ConfigError::JumpTopology(topology) => write!(
+                f,
+                "a world with the {topology:?} topology can't jump over generations, \
+                 the jumps ignoring the edges"
+            ),
+            ConfigError::Symmetry { width, height } => write!(
+                f,
+                "a soup with the c4 or d8 symmetry needs a square area, \
+                 not one of {width}x{height} cells"
+            ),
+            ConfigError::Runs {
+                sizes,
+                randomness,
+                seeds,
+            } => write!(
+                f,
+                "a sweep over {sizes} sizes, {randomness} proportions and {seeds} seeds \
+                 runs more than {} worlds",
+                sweep::MAX_RUNS
+            ),
         }
-        for i in dying_cells {
-            self.world[i.1 * self.size as usize + i.0] = CellState::Dying;
+    }
+}
+
+impl Error for ConfigError {}
+
+/// A world in which the known patterns can be spawned.
+///
+/// The coordinates are signed for the worlds which have cells
+/// on both sides of the origin.
+pub trait Spawn {
+    /// Return whether the cell at column `x` and row `y` is in the world.
+    fn contains(&self, x: i64, y: i64) -> bool;
+
+    /// Set the state of the cell at column `x` and row `y`.
+    fn spawn_cell(&mut self, x: i64, y: i64, state: CellState) -> Result<(), OutOfBounds>;
+
+    /// Spawn the `cells`, given as (column, row, state) relative to the
+    /// column `x` and the row `y`.
+    ///
+    /// Nothing is spawned if one of the cells is outside the world.
+    fn spawn_cells(
+        &mut self,
+        x: i64,
+        y: i64,
+        cells: &[(i64, i64, CellState)],
+    ) -> Result<(), OutOfBounds> {
+        let mut cells = cells.iter().map(|&(dx, dy, state)| (x + dx, y + dy, state));
+        if let Some((x, y, _)) = cells.clone().find(|&(x, y, _)| !self.contains(x, y)) {
+            return Err(OutOfBounds { x, y });
         }
+        cells.try_for_each(|(x, y, state)| self.spawn_cell(x, y, state))
+    }
+
+    /// Spawn the cells of `pattern`, its origin being at column `x` and
+    /// row `y`.
+    ///
+    /// Nothing is spawned if one of the cells is outside the world.
+    fn stamp(&mut self, pattern: &Pattern, x: i64, y: i64) -> Result<(), OutOfBounds> {
+        self.spawn_cells(x, y, pattern.cells())
+    }
+
+    /// Spawn a period-3 oscillator of 4x4 cells, whose top left corner
+    /// is at column `x` and row `y`.
+    fn spawn_osc3(&mut self, x: i64, y: i64) -> Result<(), OutOfBounds> {
+        self.spawn_cells(
+            x,
+            y,
+            &[
+                (1, 0, CellState::Alive),
+                (1, 1, CellState::Dying(0)),
+                (2, 1, CellState::Dying(0)),
+                (3, 1, CellState::Alive),
+                (0, 2, CellState::Alive),
+                (1, 2, CellState::Dying(0)),
+                (2, 2, CellState::Dying(0)),
+                (2, 3, CellState::Alive),
+            ],
+        )
+    }
+
+    /// Spawn a glider of 2x2 cells moving down, whose top left corner
+    /// is at column `x` and row `y`.
+    fn spawn_glider4_downward(&mut self, x: i64, y: i64) -> Result<(), OutOfBounds> {
+        self.spawn_cells(
+            x,
+            y,
+            &[
+                (0, 0, CellState::Dying(0)),
+                (1, 0, CellState::Dying(0)),
+                (0, 1, CellState::Alive),
+                (1, 1, CellState::Alive),
+            ],
+        )
+    }
+
+    /// Spawn a glider of 2x2 cells moving up, whose top left corner
+    /// is at column `x` and row `y`.
+    fn spawn_glider4_upward(&mut self, x: i64, y: i64) -> Result<(), OutOfBounds> {
+        self.spawn_cells(
+            x,
+            y,
+            &[
+                (0, 0, CellState::Alive),
+                (1, 0, CellState::Alive),
+                (0, 1, CellState::Dying(0)),
+                (1, 1, CellState::Dying(0)),
+            ],
+        )
+    }
+
+    /// Spawn a glider of 2x2 cells moving left, whose top left corner
+    /// is at column `x` and row `y`.
+    fn spawn_glider4_leftward(&mut self, x: i64, y: i64) -> Result<(), OutOfBounds> {
+        self.spawn_cells(
+            x,
+            y,
+            &[
+                (0, 0, CellState::Alive),
+                (1, 0, CellState::Dying(0)),
+                (0, 1, CellState::Alive),
+                (1, 1, CellState::Dying(0)),
+            ],
+        )
+    }
+
+    /// Spawn a glider of 2x2 cells moving right, whose top left corner
+    /// is at column `x` and row `y`.
+    fn spawn_glider4_rightward(&mut self, x: i64, y: i64) -> Result<(), OutOfBounds> {
+        self.spawn_cells(
+            x,
+            y,
+            &[
+                (0, 0, CellState::Dying(0)),
+                (1, 0, CellState::Alive),
+                (0, 1, CellState::Dying(0)),
+                (1, 1, CellState::Alive),
+            ],
+        )
+    }
+
+    /// Spawn a pattern of 3x3 cells burning into a wick, whose top left
+    /// corner is at column `x` and row `y`.
+    fn spawn_wick3(&mut self, x: i64, y: i64) -> Result<(), OutOfBounds> {
+        self.spawn_cells(
+            x,
+            y,
+            &[
+                (0, 0, CellState::Alive),
+                (1, 0, CellState::Alive),
+                (2, 0, CellState::Alive),
+                (0, 1, CellState::Alive),
+                (2, 1, CellState::Alive),
+                (1, 2, CellState::Dying(0)),
+            ],
+        )
     }
 }
 
@@ -411,85 +1050,752 @@ impl WorldState {
 mod tests {
 
     use super::*;
+    use cycle::Outcome;
 
     fn count(ws: &WorldState, c: CellState) -> usize {
         ws.to_string().matches(&c.to_string()).count()
     }
 
+    fn sorted_neighbours(ws: &WorldState, i: usize) -> Vec<usize> {
+        let mut neighbours: Vec<_> = ws.neighbours.get(i).collect();
+        neighbours.sort_unstable();
+        neighbours
+    }
+
     #[test]
     fn test_randomize_for_rate_equal_one() {
         let mut ws = WorldState::new(100);
-        ws.randomize(1.0);
+        ws.randomize(1.0).unwrap();
         assert_eq!(count(&ws, CellState::Alive), 10_000);
     }
 
     #[test]
     fn test_randomize_for_rate_equal_zero() {
         let mut ws = WorldState::new(100);
-        ws.randomize(0.0);
+        ws.randomize(0.0).unwrap();
         assert_eq!(count(&ws, CellState::Dead), 10_000);
     }
 
     #[test]
     fn test_randomize_for_rate_equal_one_point_five() {
         let mut ws = WorldState::new(100);
-        ws.randomize(0.5);
+        ws.randomize(0.5).unwrap();
         assert_eq!(count(&ws, CellState::Dead), 5_000);
         assert_eq!(count(&ws, CellState::Alive), 5_000);
     }
 
+    #[test]
+    fn test_randomize_outside_zero_to_one() {
+        let mut ws = WorldState::new(10);
+        assert_eq!(ws.randomize(-0.1), Err(ConfigError::Rate(-0.1)));
+        assert_eq!(ws.randomize_with_seed(1.5, 0), Err(ConfigError::Rate(1.5)));
+        assert!(matches!(ws.randomize(f64::NAN), Err(ConfigError::Rate(_))));
+        assert_eq!(ws, WorldState::new(10));
+    }
+
+    #[test]
+    fn test_try_with_dimensions() {
+        assert_eq!(
+            WorldState::try_with_dimensions(3, 2),
+            Ok(WorldState::with_dimensions(3, 2))
+        );
+        assert_eq!(
+            WorldState::try_with_dimensions(0, 5),
+            Err(ConfigError::Empty {
+                width: 0,
+                height: 5
+            })
+        );
+        assert_eq!(
+            WorldState::try_with_dimensions(100_000, 100_000),
+            Err(ConfigError::TooLarge {
+                width: 100_000,
+                height: 100_000
+            })
+        );
+    }
+
+    #[test]
+    fn test_example_by_number() {
+        assert_eq!(WorldState::example(2), Ok(WorldState::example2()));
+        assert_eq!(WorldState::example(4), Err(ConfigError::Example(4)));
+    }
+
+    #[test]
+    fn test_randomize_with_the_same_seed() {
+        let (mut ws1, mut ws2) = (WorldState::new(30), WorldState::new(30));
+        ws1.randomize_with_seed(0.3, 7).unwrap();
+        ws2.randomize_with_seed(0.3, 7).unwrap();
+        assert_eq!(ws1, ws2);
+        assert_eq!(count(&ws1, CellState::Alive), 270);
+
+        ws2 = WorldState::new(30);
+        ws2.randomize_with_seed(0.3, 8).unwrap();
+        assert_ne!(ws1, ws2);
+    }
+
+    #[test]
+    fn test_randomize_returns_its_seed() {
+        let mut ws1 = WorldState::new(30);
+        let seed = ws1.randomize(0.4).unwrap();
+        let mut ws2 = WorldState::new(30);
+        ws2.randomize_with_seed(0.4, seed).unwrap();
+        assert_eq!(ws1, ws2);
+    }
+
     #[test]
     fn test_get_neighbours_top_left_corner() {
         let ws = WorldState::new(10);
-        assert_eq!(ws.neighbours[0], vec![1, 10, 11]);
+        assert_eq!(ws.neighbours.get(0).collect::<Vec<_>>(), vec![1, 10, 11]);
     }
 
     #[test]
     fn test_get_neighbours_top_right_corner() {
         let ws = WorldState::new(10);
-        assert_eq!(ws.neighbours[9], vec![8, 18, 19]);
+        assert_eq!(ws.neighbours.get(9).collect::<Vec<_>>(), vec![8, 18, 19]);
     }
 
     #[test]
     fn test_get_neighbours_bottom_left_corner() {
         let ws = WorldState::new(10);
-        assert_eq!(ws.neighbours[90], vec![80, 81, 91]);
+        assert_eq!(ws.neighbours.get(90).collect::<Vec<_>>(), vec![80, 81, 91]);
     }
 
     #[test]
     fn test_get_neighbours_bottom_right_corner() {
         let ws = WorldState::new(10);
-        assert_eq!(ws.neighbours[99], vec![88, 89, 98]);
+        assert_eq!(ws.neighbours.get(99).collect::<Vec<_>>(), vec![88, 89, 98]);
     }
 
     #[test]
     fn test_get_neighbours_top_edge() {
         let ws = WorldState::new(10);
-        assert_eq!(ws.neighbours[4], vec![3, 5, 13, 14, 15]);
+        assert_eq!(
+            ws.neighbours.get(4).collect::<Vec<_>>(),
+            vec![3, 5, 13, 14, 15]
+        );
     }
 
     #[test]
     fn test_get_neighbours_bottom_edge() {
         let ws = WorldState::new(10);
-        assert_eq!(ws.neighbours[94], vec![83, 84, 85, 93, 95]);
+        assert_eq!(
+            ws.neighbours.get(94).collect::<Vec<_>>(),
+            vec![83, 84, 85, 93, 95]
+        );
     }
 
     #[test]
     fn test_get_neighbours_left_edge() {
         let ws = WorldState::new(10);
-        assert_eq!(ws.neighbours[50], vec![40, 41, 51, 60, 61]);
+        assert_eq!(
+            ws.neighbours.get(50).collect::<Vec<_>>(),
+            vec![40, 41, 51, 60, 61]
+        );
     }
 
     #[test]
     fn test_get_neighbours_right_edge() {
         let ws = WorldState::new(10);
-        assert_eq!(ws.neighbours[59], vec![48, 49, 58, 68, 69]);
+        assert_eq!(
+            ws.neighbours.get(59).collect::<Vec<_>>(),
+            vec![48, 49, 58, 68, 69]
+        );
     }
 
     #[test]
     fn test_get_neighbours_general_case() {
         let ws = WorldState::new(10);
-        assert_eq!(ws.neighbours[55], vec![44, 45, 46, 54, 56, 64, 65, 66]);
+        assert_eq!(
+            ws.neighbours.get(55).collect::<Vec<_>>(),
+            vec![44, 45, 46, 54, 56, 64, 65, 66]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_torus_top_left_corner() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::Torus);
+        assert_eq!(
+            sorted_neighbours(&ws, 0),
+            vec![1, 9, 10, 11, 19, 90, 91, 99]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_torus_top_right_corner() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::Torus);
+        assert_eq!(
+            sorted_neighbours(&ws, 9),
+            vec![0, 8, 10, 18, 19, 90, 98, 99]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_torus_bottom_left_corner() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::Torus);
+        assert_eq!(
+            sorted_neighbours(&ws, 90),
+            vec![0, 1, 9, 80, 81, 89, 91, 99]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_torus_bottom_right_corner() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::Torus);
+        assert_eq!(
+            sorted_neighbours(&ws, 99),
+            vec![0, 8, 9, 80, 88, 89, 90, 98]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_torus_top_edge() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::Torus);
+        assert_eq!(
+            sorted_neighbours(&ws, 4),
+            vec![3, 5, 13, 14, 15, 93, 94, 95]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_torus_bottom_edge() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::Torus);
+        assert_eq!(
+            sorted_neighbours(&ws, 94),
+            vec![3, 4, 5, 83, 84, 85, 93, 95]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_torus_left_edge() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::Torus);
+        assert_eq!(
+            sorted_neighbours(&ws, 50),
+            vec![40, 41, 49, 51, 59, 60, 61, 69]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_torus_right_edge() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::Torus);
+        assert_eq!(
+            sorted_neighbours(&ws, 59),
+            vec![40, 48, 49, 50, 58, 60, 68, 69]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_torus_general_case() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::Torus);
+        assert_eq!(
+            sorted_neighbours(&ws, 55),
+            vec![44, 45, 46, 54, 56, 64, 65, 66]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_klein_bottle_top_left_corner() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::KleinBottle);
+        assert_eq!(
+            sorted_neighbours(&ws, 0),
+            vec![1, 9, 10, 11, 19, 90, 98, 99]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_klein_bottle_top_right_corner() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::KleinBottle);
+        assert_eq!(
+            sorted_neighbours(&ws, 9),
+            vec![0, 8, 10, 18, 19, 90, 91, 99]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_klein_bottle_bottom_left_corner() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::KleinBottle);
+        assert_eq!(
+            sorted_neighbours(&ws, 90),
+            vec![0, 8, 9, 80, 81, 89, 91, 99]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_klein_bottle_bottom_right_corner() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::KleinBottle);
+        assert_eq!(
+            sorted_neighbours(&ws, 99),
+            vec![0, 1, 9, 80, 88, 89, 90, 98]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_klein_bottle_top_edge() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::KleinBottle);
+        assert_eq!(
+            sorted_neighbours(&ws, 4),
+            vec![3, 5, 13, 14, 15, 94, 95, 96]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_klein_bottle_bottom_edge() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::KleinBottle);
+        assert_eq!(
+            sorted_neighbours(&ws, 94),
+            vec![4, 5, 6, 83, 84, 85, 93, 95]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_klein_bottle_left_edge() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::KleinBottle);
+        assert_eq!(
+            sorted_neighbours(&ws, 50),
+            vec![40, 41, 49, 51, 59, 60, 61, 69]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_klein_bottle_right_edge() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::KleinBottle);
+        assert_eq!(
+            sorted_neighbours(&ws, 59),
+            vec![40, 48, 49, 50, 58, 60, 68, 69]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_klein_bottle_general_case() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::KleinBottle);
+        assert_eq!(
+            sorted_neighbours(&ws, 55),
+            vec![44, 45, 46, 54, 56, 64, 65, 66]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_projective_plane_top_left_corner() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::ProjectivePlane);
+        assert_eq!(
+            sorted_neighbours(&ws, 0),
+            vec![0, 1, 10, 11, 89, 98, 99, 99]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_projective_plane_top_right_corner() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::ProjectivePlane);
+        assert_eq!(
+            sorted_neighbours(&ws, 9),
+            vec![8, 9, 18, 19, 80, 90, 90, 91]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_projective_plane_bottom_left_corner() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::ProjectivePlane);
+        assert_eq!(
+            sorted_neighbours(&ws, 90),
+            vec![8, 9, 9, 19, 80, 81, 90, 91]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_projective_plane_bottom_right_corner() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::ProjectivePlane);
+        assert_eq!(
+            sorted_neighbours(&ws, 99),
+            vec![0, 0, 1, 10, 88, 89, 98, 99]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_projective_plane_top_edge() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::ProjectivePlane);
+        assert_eq!(
+            sorted_neighbours(&ws, 4),
+            vec![3, 5, 13, 14, 15, 94, 95, 96]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_projective_plane_bottom_edge() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::ProjectivePlane);
+        assert_eq!(
+            sorted_neighbours(&ws, 94),
+            vec![4, 5, 6, 83, 84, 85, 93, 95]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_projective_plane_left_edge() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::ProjectivePlane);
+        assert_eq!(
+            sorted_neighbours(&ws, 50),
+            vec![39, 40, 41, 49, 51, 59, 60, 61]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_projective_plane_right_edge() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::ProjectivePlane);
+        assert_eq!(
+            sorted_neighbours(&ws, 59),
+            vec![30, 40, 48, 49, 50, 58, 68, 69]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_projective_plane_general_case() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::ProjectivePlane);
+        assert_eq!(
+            sorted_neighbours(&ws, 55),
+            vec![44, 45, 46, 54, 56, 64, 65, 66]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_reflective_top_left_corner() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::Reflective);
+        assert_eq!(sorted_neighbours(&ws, 0), vec![0, 0, 0, 1, 1, 10, 10, 11]);
+    }
+
+    #[test]
+    fn test_get_neighbours_reflective_top_right_corner() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::Reflective);
+        assert_eq!(sorted_neighbours(&ws, 9), vec![8, 8, 9, 9, 9, 18, 19, 19]);
+    }
+
+    #[test]
+    fn test_get_neighbours_reflective_bottom_left_corner() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::Reflective);
+        assert_eq!(
+            sorted_neighbours(&ws, 90),
+            vec![80, 80, 81, 90, 90, 90, 91, 91]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_reflective_bottom_right_corner() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::Reflective);
+        assert_eq!(
+            sorted_neighbours(&ws, 99),
+            vec![88, 89, 89, 98, 98, 99, 99, 99]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_reflective_top_edge() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::Reflective);
+        assert_eq!(sorted_neighbours(&ws, 4), vec![3, 3, 4, 5, 5, 13, 14, 15]);
+    }
+
+    #[test]
+    fn test_get_neighbours_reflective_bottom_edge() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::Reflective);
+        assert_eq!(
+            sorted_neighbours(&ws, 94),
+            vec![83, 84, 85, 93, 93, 94, 95, 95]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_reflective_left_edge() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::Reflective);
+        assert_eq!(
+            sorted_neighbours(&ws, 50),
+            vec![40, 40, 41, 50, 51, 60, 60, 61]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_reflective_right_edge() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::Reflective);
+        assert_eq!(
+            sorted_neighbours(&ws, 59),
+            vec![48, 49, 49, 58, 59, 68, 69, 69]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_reflective_general_case() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::Reflective);
+        assert_eq!(
+            sorted_neighbours(&ws, 55),
+            vec![44, 45, 46, 54, 56, 64, 65, 66]
+        );
+    }
+
+    #[test]
+    fn test_glider_crosses_torus_edge() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::Torus);
+        ws.spawn_glider4_downward(4, 4).unwrap();
+        for _ in 0..25 {
+            ws.next();
+        }
+        assert_eq!(count(&ws, CellState::Alive), 2);
+        assert_eq!(count(&ws, CellState::Dying(0)), 2);
+    }
+
+    #[test]
+    fn test_glider_dies_on_bounded_edge() {
+        let mut ws = WorldState::new(10);
+        ws.spawn_glider4_downward(4, 4).unwrap();
+        for _ in 0..25 {
+            ws.next();
+        }
+        assert_eq!(count(&ws, CellState::Dead), 100);
+    }
+
+    #[test]
+    fn test_get_neighbours_world_larger_than_u16_squared() {
+        let ws = WorldState::new(300);
+        assert_eq!(
+            ws.neighbours.get(89_999).collect::<Vec<_>>(),
+            vec![89_698, 89_699, 89_998]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_rectangular_world() {
+        let ws = WorldState::with_dimensions(10, 5);
+        assert_eq!(ws.neighbours.get(9).collect::<Vec<_>>(), vec![8, 18, 19]);
+        assert_eq!(ws.neighbours.get(49).collect::<Vec<_>>(), vec![38, 39, 48]);
+        assert_eq!(
+            ws.neighbours.get(25).collect::<Vec<_>>(),
+            vec![14, 15, 16, 24, 26, 34, 35, 36]
+        );
+    }
+
+    #[test]
+    fn test_parse_display_round_trip() {
+        for ws in [
+            WorldState::example1(),
+            WorldState::example2(),
+            WorldState::example3(),
+        ] {
+            assert_eq!(ws.to_string().parse(), Ok(ws));
+        }
+
+        let mut ws = WorldState::with_dimensions(4, 2);
+        ws.set_rule("B2/S/C5".parse().unwrap());
+        ws.spawn_cell(1, 0, CellState::Dying(2)).unwrap();
+        let mut parsed: WorldState = ws.to_string().parse().unwrap();
+        parsed.set_rule(ws.rule().clone());
+        assert_eq!(parsed, ws);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "O.\n.#\n".parse::<WorldState>(),
+            Err(ParseWorldError::Cell('#'))
+        );
+        assert_eq!(
+            "O.\n.\n".parse::<WorldState>(),
+            Err(ParseWorldError::Ragged(1))
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_von_neumann() {
+        let mut ws = WorldState::new(10);
+        ws.set_neighbourhood(Neighbourhood::VonNeumann(1));
+        assert_eq!(ws.neighbours.get(0).collect::<Vec<_>>(), vec![1, 10]);
+        assert_eq!(
+            ws.neighbours.get(55).collect::<Vec<_>>(),
+            vec![45, 54, 56, 65]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_moore_radius_2_on_torus() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::Torus);
+        ws.set_neighbourhood(Neighbourhood::Moore(2));
+        let neighbours = sorted_neighbours(&ws, 0);
+        assert_eq!(neighbours.len(), 24);
+        assert!(neighbours.contains(&88));
+        assert!(neighbours.contains(&22));
+        assert!(!neighbours.contains(&0));
+    }
+
+    #[test]
+    fn test_get_neighbours_hexagonal() {
+        let mut ws = WorldState::new(10);
+        ws.set_neighbourhood(Neighbourhood::Hexagonal);
+        // even rows see the cells above and below on their left.
+        assert_eq!(
+            ws.neighbours.get(44).collect::<Vec<_>>(),
+            vec![33, 34, 43, 45, 53, 54]
+        );
+        // odd rows see the cells above and below on their right.
+        assert_eq!(
+            ws.neighbours.get(55).collect::<Vec<_>>(),
+            vec![45, 46, 54, 56, 65, 66]
+        );
+    }
+
+    #[test]
+    fn test_get_neighbours_custom_mask() {
+        let mut ws = WorldState::new(10);
+        ws.set_neighbourhood("custom:2,0;0,-3;0,0".parse().unwrap());
+        assert_eq!(ws.neighbours.get(0).collect::<Vec<_>>(), vec![2, 0]);
+        assert_eq!(ws.neighbours.get(55).collect::<Vec<_>>(), vec![57, 25, 55]);
+    }
+
+    #[test]
+    fn test_neighbours_found_without_a_table() {
+        for topology in [Topology::Bounded, Topology::KleinBottle] {
+            for neighbourhood in ["moore:3", "hexagonal", "custom:2,0;0,-3;0,0"] {
+                let neighbourhood: Neighbourhood = neighbourhood.parse().unwrap();
+                let table = WorldState::precompute_neighbours(9, 7, topology, &neighbourhood);
+                for i in 0..63 {
+                    assert_eq!(
+                        table.get(i).collect::<Vec<_>>(),
+                        table.compute(i).collect::<Vec<_>>()
+                    );
+                }
+            }
+        }
+
+        // a table of 10^10 slots would be too large.
+        let mut ws = WorldState::new(1000);
+        ws.set_neighbourhood(Neighbourhood::Moore(50));
+        assert_eq!(ws.neighbours.indexes, None);
+        assert_eq!(ws.neighbours.get(0).count(), 51 * 51 - 1);
+    }
+
+    #[test]
+    fn test_display_rectangular_world() {
+        let mut ws = WorldState::with_dimensions(3, 2);
+        ws.randomize(1.0).unwrap();
+        assert_eq!(ws.to_string(), "OOO\nOOO\n");
+    }
+
+    #[test]
+    fn test_next_parallel_same_as_next() {
+        for (threads, rule) in [1, 2, 3, 7, 100]
+            .into_iter()
+            .flat_map(|threads| [(threads, "B2/S/C3"), (threads, "B2/S3/C4")])
+        {
+            let mut ws = WorldState::with_dimensions(40, 25);
+            ws.set_rule(rule.parse().unwrap());
+            ws.randomize(0.4).unwrap();
+            let mut parallel = ws.clone();
+            for _ in 0..15 {
+                ws.next();
+                parallel.next_parallel(threads);
+                assert_eq!(parallel, ws);
+            }
+        }
+    }
+
+    #[test]
+    fn test_next_keeps_its_bit_world_in_step() {
+        let mut ws = WorldState::with_dimensions(70, 9);
+        ws.set_topology(Topology::Torus);
+        ws.randomize_with_seed(0.4, 7).unwrap();
+        let mut by_cell = ws.clone();
+        for generation in 0..20 {
+            ws.next();
+            by_cell.next_by_cell();
+            assert_eq!(ws, by_cell);
+
+            // cells set between generations are seen by the next one.
+            let (x, y) = (generation * 13 % 70, generation % 9);
+            for state in [CellState::Alive, CellState::Dying(0), CellState::Dead] {
+                ws.set(x, y, state).unwrap();
+                by_cell.set(x, y, state).unwrap();
+                ws.set(69 - x, 8 - y, state).unwrap();
+                by_cell.set(69 - x, 8 - y, state).unwrap();
+            }
+            ws.set(x, y, CellState::Alive).unwrap();
+            by_cell.set(x, y, CellState::Alive).unwrap();
+        }
+        assert!(ws.bits.is_some());
+
+        ws.set_rule("B2/S3/C4".parse().unwrap());
+        assert!(ws.bits.is_none());
+    }
+
+    #[test]
+    fn test_blinker_with_game_of_life_rule() {
+        let mut ws = WorldState::with_dimensions(5, 5);
+        ws.set_rule("B3/S23/C2".parse().unwrap());
+        for x in 1..4 {
+            ws.spawn_cell(x, 2, CellState::Alive).unwrap();
+        }
+        let horizontal = ws.clone();
+        ws.next();
+        assert_eq!(ws.to_string(), ".....\n..O..\n..O..\n..O..\n.....\n");
+        ws.next();
+        assert_eq!(ws, horizontal);
+    }
+
+    #[test]
+    fn test_dying_stages_with_more_states() {
+        let mut ws = WorldState::with_dimensions(1, 1);
+        ws.set_rule("B2/S/C5".parse().unwrap());
+        ws.randomize(1.0).unwrap();
+        let mut displays = vec![];
+        for _ in 0..5 {
+            displays.push(ws.to_string());
+            ws.next();
+        }
+        assert_eq!(displays, ["O\n", "X\n", "a\n", "b\n", ".\n"]);
+    }
+
+    #[test]
+    fn test_as_vertices_fades_dying_stages() {
+        let mut ws = WorldState::with_dimensions(1, 1);
+        ws.set_rule("B2/S/C4".parse().unwrap());
+        ws.randomize(1.0).unwrap();
+        ws.next();
+        assert!(ws.as_vertices().iter().all(|v| v.color == DYING_COLOR));
+        ws.next();
+        assert!(ws
+            .as_vertices()
+            .iter()
+            .all(|v| v.color == [DYING_COLOR[0] / 2.0, 0.0, 0.0]));
     }
 
     #[test]
@@ -497,7 +1803,7 @@ mod tests {
         // declare a world with just one cell.
         let mut ws = WorldState::new(1);
         // set the cell to On state.
-        ws.randomize(1.0);
+        ws.randomize(1.0).unwrap();
         let cells = ws.as_vertices();
         assert_eq!(cells.len(), 6);
         // advance to next iteration: the cell must be in dying mode.
@@ -514,7 +1820,7 @@ mod tests {
     #[test]
     fn test_as_vertices_good_coordinates_for_one_cell_world() {
         let mut ws = WorldState::new(1);
-        ws.randomize(1.0);
+        ws.randomize(1.0).unwrap();
         let cells = ws.as_vertices();
         assert!(cells.contains(&Vertex {
             position: [-1.0, -1.0],
@@ -552,10 +1858,48 @@ mod tests {
         }));
     }
 
+    #[test]
+    fn test_as_vertices_keeps_cells_square_for_rectangular_world() {
+        let mut ws = WorldState::with_dimensions(2, 1);
+        ws.randomize(1.0).unwrap();
+        let cells = ws.as_vertices();
+        assert_eq!(cells.len(), 12);
+        for position in [[-1.0, -0.5], [-1.0, 0.5], [1.0, -0.5], [1.0, 0.5]] {
+            assert!(cells.contains(&Vertex {
+                position,
+                color: ALIVE_COLOR
+            }));
+        }
+        assert!(cells.iter().all(|v| v.position[1].abs() <= 0.5));
+    }
+
+    #[test]
+    fn test_as_vertices_draws_hexagons() {
+        let mut ws = WorldState::with_dimensions(3, 3);
+        ws.set_neighbourhood(Neighbourhood::Hexagonal);
+        ws.randomize(1.0).unwrap();
+        let cells = ws.as_vertices();
+        assert_eq!(cells.len(), 9 * 12);
+        // the shifted row touches the right edge, the others the left edge.
+        let xs = cells.iter().map(|v| v.position[0]);
+        let ys = cells.iter().map(|v| v.position[1]);
+        assert!((xs.clone().fold(f32::MAX, f32::min) + 1.0).abs() < 1e-6);
+        assert!((xs.fold(f32::MIN, f32::max) - 1.0).abs() < 1e-6);
+        assert!(ys.map(f32::abs).all(|y| y < 1.0));
+    }
+
+    #[test]
+    fn test_spawn_osc3_at_column_x_and_row_y() {
+        let mut ws = WorldState::with_dimensions(6, 5);
+        ws.spawn_osc3(2, 1).unwrap();
+        assert_eq!(ws.to_string(), "......\n...O..\n...XXO\n..OXX.\n....O.\n");
+        assert_eq!(ws.spawn_osc3(3, 1), Err(OutOfBounds { x: 6, y: 2 }));
+    }
+
     #[test]
     fn test_spawn_osc3() {
         let mut ws = WorldState::new(4);
-        ws.spawn_osc3(0, 0);
+        ws.spawn_osc3(0, 0).unwrap();
         let init_ws = ws.clone();
 
         assert_eq!(init_ws, ws); // initially worlds are equal
@@ -566,4 +1910,19 @@ mod tests {
         ws.next();
         assert_eq!(init_ws, ws); // iter #3 worlds are equal again
     }
+
+    #[test]
+    fn test_spawn_osc3_cycle() {
+        let mut ws = WorldState::new(4);
+        ws.spawn_osc3(0, 0).unwrap();
+        assert_eq!(
+            ws.find_cycle(10),
+            Some(Outcome::Cycle {
+                transient: 0,
+                period: 3,
+                dx: 0,
+                dy: 0
+            })
+        );
+    }
 }
@@ -0,0 +1,523 @@
+use super::{CellState, WorldState};
+use crate::neighbourhood::Neighbourhood;
+use crate::rule::Rule;
+use crate::topology::Topology;
+
+use std::ops::Range;
+use std::thread;
+
+/// The number of cells stored in one word of a bitplane.
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// The cell seen from inside the world at a position outside of it,
+/// if there's any.
+type Ghost = Option<(usize, usize)>;
+
+/// A bit-packed representation of the Cellular Automaton, much faster to
+/// advance than a **WorldState**.
+///
+/// Each generation is stored as two bitplanes, one for the cells **Alive**
+/// and one for the cells **Dying**. The cells **Dead** are the ones set in
+/// neither plane. Every row starts on a new word, and the bits past the
+/// end of a row are always 0.
+///
+/// The next generation is written into a second pair of bitplanes, which
+/// are then swapped with the current ones, so that no allocation happens
+/// while the world is running.
+#[derive(Clone, Debug)]
+pub struct BitWorld {
+    width: usize,
+    height: usize,
+    topology: Topology,
+
+    /// The number of words used by one row of a bitplane.
+    row_words: usize,
+
+    alive: Vec<u64>,
+    dying: Vec<u64>,
+    next_alive: Vec<u64>,
+    next_dying: Vec<u64>,
+
+    /// For each row, the cells seen past its left and right edges.
+    ghost_columns: Vec<(Ghost, Ghost)>,
+
+    /// The cells seen in the row above the world, from `x = -1` to
+    /// `x = width` (both corners included).
+    ghost_top: Vec<Ghost>,
+
+    /// The cells seen in the row below the world, from `x = -1` to
+    /// `x = width` (both corners included).
+    ghost_bottom: Vec<Ghost>,
+
+    /// Three rows of **Alive** cells surrounded by their ghost cells,
+    /// reused from one row to the next while computing a generation.
+    halos: [Vec<u64>; 3],
+}
+
+impl BitWorld {
+    /// Create a new **BitWorld** of `width` x `height` dead cells.
+    fn new(width: usize, height: usize, topology: Topology) -> BitWorld {
+        let row_words = width.div_ceil(WORD_BITS);
+        let (w, h) = (width as isize, height as isize);
+        let has_cells = width > 0 && height > 0;
+
+        let ghost_row = |y: isize| -> Vec<Ghost> {
+            if has_cells {
+                (-1..=w)
+                    .map(|x| topology.wrap(x, y, width, height))
+                    .collect()
+            } else {
+                vec![]
+            }
+        };
+        let ghost_columns = (0..h)
+            .map(|y| {
+                if has_cells {
+                    (
+                        topology.wrap(-1, y, width, height),
+                        topology.wrap(w, y, width, height),
+                    )
+                } else {
+                    (None, None)
+                }
+            })
+            .collect();
+
+        // a halo row holds `width + 2` bits, plus one word of padding
+        // so that shifting a word can always read the following one.
+        let halo = vec![0; row_words + 1];
+
+        BitWorld {
+            width,
+            height,
+            topology,
+            row_words,
+            alive: vec![0; row_words * height],
+            dying: vec![0; row_words * height],
+            next_alive: vec![0; row_words * height],
+            next_dying: vec![0; row_words * height],
+            ghost_columns,
+            ghost_top: ghost_row(-1),
+            ghost_bottom: ghost_row(h),
+            halos: [halo.clone(), halo.clone(), halo],
+        }
+    }
+
+    /// Return the position of the cell (`x`, `y`) in the bitplanes, as the
+    /// index of its word and the mask of its bit.
+    fn locate(&self, x: usize, y: usize) -> (usize, u64) {
+        (y * self.row_words + x / WORD_BITS, 1 << (x % WORD_BITS))
+    }
+
+    /// Change the state of the cell (`x`, `y`), which is in the world.
+    /// Every stage of **Dying** is stored as **Dying**.
+    pub(super) fn set(&mut self, x: usize, y: usize, state: CellState) {
+        let (word, bit) = self.locate(x, y);
+        self.alive[word] &= !bit;
+        self.dying[word] &= !bit;
+        match state {
+            CellState::Alive => self.alive[word] |= bit,
+            CellState::Dying(_) => self.dying[word] |= bit,
+            CellState::Dead => {}
+        }
+    }
+
+    fn is_alive(&self, x: usize, y: usize) -> bool {
+        let (word, bit) = self.locate(x, y);
+        self.alive[word] & bit != 0
+    }
+
+    /// Fill `halo` with the **Alive** cells of the row `y`, shifted by one
+    /// bit to leave room for the ghost cell seen past the left edge.
+    /// The row `y` can be just outside the world, in which case the
+    /// ghost cells above or below the world are used.
+    fn fill_halo(&self, y: isize, halo: &mut [u64]) {
+        halo.fill(0);
+
+        if (0..self.height as isize).contains(&y) {
+            let y = y as usize;
+            let row = &self.alive[y * self.row_words..(y + 1) * self.row_words];
+            let mut carry = 0;
+            for (k, word) in row.iter().enumerate() {
+                halo[k] = (word << 1) | carry;
+                carry = word >> (WORD_BITS - 1);
+            }
+            halo[self.row_words] = carry;
+
+            let (left, right) = self.ghost_columns[y];
+            if left.is_some_and(|(gx, gy)| self.is_alive(gx, gy)) {
+                halo[0] |= 1;
+            }
+            if right.is_some_and(|(gx, gy)| self.is_alive(gx, gy)) {
+                let j = self.width + 1;
+                halo[j / WORD_BITS] |= 1 << (j % WORD_BITS);
+            }
+        } else {
+            let ghosts = if y < 0 {
+                &self.ghost_top
+            } else {
+                &self.ghost_bottom
+            };
+            for (j, ghost) in ghosts.iter().enumerate() {
+                if ghost.is_some_and(|(gx, gy)| self.is_alive(gx, gy)) {
+                    halo[j / WORD_BITS] |= 1 << (j % WORD_BITS);
+                }
+            }
+        }
+    }
+
+    /// Compute the next state of the rows in `rows`, and write it into
+    /// `next_alive` and `next_dying`, which only hold these rows.
+    ///
+    /// The halo rows just above and below the band are read from the
+    /// current generation, so several bands can be computed at once.
+    fn next_band(
+        &self,
+        rows: Range<usize>,
+        next_alive: &mut [u64],
+        next_dying: &mut [u64],
+        halos: &mut [Vec<u64>; 3],
+    ) {
+        let last_word_mask = match self.width % WORD_BITS {
+            0 => u64::MAX,
+            r => (1 << r) - 1,
+        };
+
+        self.fill_halo(rows.start as isize - 1, &mut halos[0]);
+        self.fill_halo(rows.start as isize, &mut halos[1]);
+        for y in rows.clone() {
+            self.fill_halo(y as isize + 1, &mut halos[2]);
+
+            for k in 0..self.row_words {
+                let i = y * self.row_words + k;
+                let [above, row, below] = &*halos;
+                let neighbours = [
+                    shifted(above, k, 0),
+                    shifted(above, k, 1),
+                    shifted(above, k, 2),
+                    shifted(row, k, 0),
+                    shifted(row, k, 2),
+                    shifted(below, k, 0),
+                    shifted(below, k, 1),
+                    shifted(below, k, 2),
+                ];
+
+                let mut dead = !(self.alive[i] | self.dying[i]);
+                if k == self.row_words - 1 {
+                    dead &= last_word_mask;
+                }
+                let j = i - rows.start * self.row_words;
+                next_alive[j] = dead & exactly_two(neighbours);
+                next_dying[j] = self.alive[i];
+            }
+
+            halos.rotate_left(1);
+        }
+    }
+
+    /// Make the bitplanes holding the next generation the current ones.
+    fn swap_generations(&mut self) {
+        std::mem::swap(&mut self.alive, &mut self.next_alive);
+        std::mem::swap(&mut self.dying, &mut self.next_dying);
+    }
+
+    /// Advance the world to its next state, following the same rules
+    /// as `WorldState::next()`.
+    pub fn next(&mut self) {
+        let mut halos = std::mem::take(&mut self.halos);
+        let mut next_alive = std::mem::take(&mut self.next_alive);
+        let mut next_dying = std::mem::take(&mut self.next_dying);
+
+        self.next_band(0..self.height, &mut next_alive, &mut next_dying, &mut halos);
+
+        self.halos = halos;
+        self.next_alive = next_alive;
+        self.next_dying = next_dying;
+        self.swap_generations();
+    }
+
+    /// Advance the world to its next state like `BitWorld::next()`, but
+    /// split the rows into `threads` bands computed at the same time.
+    pub fn next_parallel(&mut self, threads: usize) {
+        if threads <= 1 {
+            return self.next();
+        }
+
+        let band_rows = self.height.div_ceil(threads).max(1);
+        let band_words = (band_rows * self.row_words).max(1);
+        let mut next_alive = std::mem::take(&mut self.next_alive);
+        let mut next_dying = std::mem::take(&mut self.next_dying);
+
+        thread::scope(|scope| {
+            let bands = next_alive
+                .chunks_mut(band_words)
+                .zip(next_dying.chunks_mut(band_words));
+            for (b, (alive, dying)) in bands.enumerate() {
+                let bw = &*self;
+                scope.spawn(move || {
+                    let first_row = b * band_rows;
+                    let rows = first_row..(first_row + band_rows).min(bw.height);
+                    let mut halos = bw.halos.clone();
+                    bw.next_band(rows, alive, dying, &mut halos);
+                });
+            }
+        });
+
+        self.next_alive = next_alive;
+        self.next_dying = next_dying;
+        self.swap_generations();
+    }
+
+    /// Write the cells into `world`, the cells of a **WorldState** of the
+    /// same dimensions, row by row.
+    pub(super) fn copy_cells(&self, world: &mut [CellState]) {
+        let words = world
+            .chunks_mut(self.width.max(1))
+            .flat_map(|row| row.chunks_mut(WORD_BITS));
+        for ((cells, alive), dying) in words.zip(&self.alive).zip(&self.dying) {
+            for (b, cell) in cells.iter_mut().enumerate() {
+                *cell = match (alive >> b & 1, dying >> b & 1) {
+                    (1, _) => CellState::Alive,
+                    (_, 1) => CellState::Dying(0),
+                    _ => CellState::Dead,
+                };
+            }
+        }
+    }
+
+    /// Write the cells which changed in the last generation into `world`,
+    /// which holds the cells of the generation before, as `copy_cells()`
+    /// would have written them.
+    ///
+    /// The bitplanes holding the next generation are the ones of the
+    /// generation before, until the world is advanced again.
+    pub(super) fn copy_changes(&self, world: &mut [CellState]) {
+        let words = self.alive.iter().zip(&self.dying);
+        let before = self.next_alive.iter().zip(&self.next_dying);
+        for (i, ((alive, dying), (was_alive, was_dying))) in words.zip(before).enumerate() {
+            let mut changed = (alive ^ was_alive) | (dying ^ was_dying);
+            let first_cell = (i / self.row_words) * self.width + (i % self.row_words) * WORD_BITS;
+            while changed != 0 {
+                let b = changed.trailing_zeros();
+                changed &= changed - 1;
+                world[first_cell + b as usize] = match (alive >> b & 1, dying >> b & 1) {
+                    (1, _) => CellState::Alive,
+                    (_, 1) => CellState::Dying(0),
+                    _ => CellState::Dead,
+                };
+            }
+        }
+    }
+}
+
+/// Return the word `k` of `halo` shifted right by `shift` bits, the
+/// missing high bits being taken from the word `k + 1`.
+fn shifted(halo: &[u64], k: usize, shift: usize) -> u64 {
+    match shift {
+        0 => halo[k],
+        _ => (halo[k] >> shift) | (halo[k + 1] << (WORD_BITS - shift)),
+    }
+}
+
+/// Return the bits set in exactly two of the `words`.
+///
+/// The words are summed bit by bit with a 2-bit counter (`ones`, `twos`),
+/// and the bits whose counter overflowed are remembered in `more`.
+fn exactly_two(words: [u64; 8]) -> u64 {
+    let (mut ones, mut twos, mut more) = (0, 0, 0);
+    for word in words {
+        let carry = ones & word;
+        ones ^= word;
+        more |= twos & carry;
+        twos ^= carry;
+    }
+    twos & !ones & !more
+}
+
+/// Copy the cells and the topology of a **WorldState**.
+///
+/// # Panics
+///
+/// Panics if the **WorldState** doesn't follow the rule of Brian's Brain
+/// in the Moore neighbourhood,
+/// the only one a **BitWorld** computes.
+impl From<&WorldState> for BitWorld {
+    fn from(ws: &WorldState) -> BitWorld {
+        assert!(
+            ws.rule() == &Rule::default() && ws.neighbourhood() == &Neighbourhood::default(),
+            "a BitWorld only follows the rule and the neighbourhood of Brian's Brain"
+        );
+        let mut bw = BitWorld::new(ws.width, ws.height, ws.topology);
+        let words = ws.rows().flat_map(|row| row.chunks(WORD_BITS));
+        for ((cells, alive), dying) in words.zip(&mut bw.alive).zip(&mut bw.dying) {
+            for (b, cell) in cells.iter().enumerate() {
+                *alive |= ((*cell == CellState::Alive) as u64) << b;
+                *dying |= (matches!(cell, CellState::Dying(_)) as u64) << b;
+            }
+        }
+        bw
+    }
+}
+
+impl From<&BitWorld> for WorldState {
+    fn from(bw: &BitWorld) -> WorldState {
+        let mut ws = WorldState::with_dimensions(bw.width, bw.height);
+        ws.set_topology(bw.topology);
+        bw.copy_cells(&mut ws.world);
+        ws
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    /// Run a **WorldState** one cell at a time and its **BitWorld** side by
+    /// side, and check that they stay identical at every generation.
+    fn assert_same_evolution(mut ws: WorldState, generations: usize) {
+        let mut bw = BitWorld::from(&ws);
+        assert_eq!(WorldState::from(&bw), ws);
+        for _ in 0..generations {
+            ws.next_by_cell();
+            bw.next();
+            assert_eq!(WorldState::from(&bw), ws);
+        }
+    }
+
+    /// Advance `ws` as the first version of `WorldState::next()` did,
+    /// before the rules and the neighbourhoods: the cells are sorted into
+    /// the ones turning **Alive**, **Dying** and **Dead**, then updated.
+    /// The 8 neighbours of a cell are wrapped by the topology of `ws`.
+    fn baseline_next(ws: &mut WorldState) {
+        let (width, height) = (ws.width, ws.height);
+        let (mut alive, mut dying, mut dead) = (vec![], vec![], vec![]);
+
+        for i in 0..ws.world.len() {
+            match ws.world[i] {
+                CellState::Alive => {
+                    dying.push(i);
+                }
+                CellState::Dead => {
+                    let (x, y) = ((i % width) as isize, (i / width) as isize);
+                    let alives = (-1..=1)
+                        .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+                        .filter(|&offset| offset != (0, 0))
+                        .filter_map(|(dx, dy)| ws.topology.wrap(x + dx, y + dy, width, height))
+                        .filter(|&(nx, ny)| ws.world[ny * width + nx] == CellState::Alive)
+                        .count();
+
+                    if alives == 2 {
+                        alive.push(i);
+                    }
+                }
+                CellState::Dying(_) => {
+                    dead.push(i);
+                }
+            }
+        }
+
+        // update the world
+        alive.iter().for_each(|&n| ws.world[n] = CellState::Alive);
+        dying
+            .iter()
+            .for_each(|&n| ws.world[n] = CellState::Dying(0));
+        dead.iter().for_each(|&n| ws.world[n] = CellState::Dead);
+    }
+
+    #[test]
+    fn test_same_evolution_as_the_baseline() {
+        let topologies = [
+            Topology::Bounded,
+            Topology::Torus,
+            Topology::KleinBottle,
+            Topology::ProjectivePlane,
+            Topology::Reflective,
+        ];
+        let sizes = [
+            (1, 1),
+            (1, 9),
+            (9, 1),
+            (2, 2),
+            (63, 3),
+            (64, 64),
+            (65, 17),
+            (129, 40),
+        ];
+        for (seed, (topology, (width, height))) in topologies
+            .into_iter()
+            .flat_map(|topology| sizes.map(|size| (topology, size)))
+            .enumerate()
+        {
+            let mut ws = WorldState::with_dimensions(width, height);
+            ws.set_topology(topology);
+            ws.randomize_with_seed(0.35, seed as u64).unwrap();
+            let mut bw = BitWorld::from(&ws);
+            let mut stepped = ws.clone();
+            for generation in 0..40 {
+                baseline_next(&mut ws);
+                bw.next();
+                stepped.next();
+                let context = format!("{topology:?} {width}x{height}, generation {generation}");
+                assert_eq!(WorldState::from(&bw), ws, "{context}");
+                assert_eq!(stepped, ws, "{context}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_exactly_two() {
+        let words = [0b1111, 0b1110, 0b1100, 0b1000, 0, 0, 0, 0];
+        assert_eq!(exactly_two(words), 0b0010);
+    }
+
+    #[test]
+    fn test_same_evolution_for_random_worlds() {
+        for (width, height) in [(1, 1), (3, 7), (63, 20), (64, 64), (65, 33), (130, 70)] {
+            let mut ws = WorldState::with_dimensions(width, height);
+            ws.randomize(0.3).unwrap();
+            assert_same_evolution(ws, 30);
+        }
+    }
+
+    #[test]
+    fn test_same_evolution_for_every_topology() {
+        for topology in [
+            Topology::Bounded,
+            Topology::Torus,
+            Topology::KleinBottle,
+            Topology::ProjectivePlane,
+            Topology::Reflective,
+        ] {
+            for (width, height) in [(2, 2), (37, 19), (64, 5), (100, 100)] {
+                let mut ws = WorldState::with_dimensions(width, height);
+                ws.set_topology(topology);
+                ws.randomize(0.2).unwrap();
+                assert_same_evolution(ws, 30);
+            }
+        }
+    }
+
+    #[test]
+    fn test_next_parallel_same_as_next() {
+        for threads in [1, 2, 3, 8, 200] {
+            let mut ws = WorldState::with_dimensions(150, 97);
+            ws.set_topology(Topology::Torus);
+            ws.randomize(0.3).unwrap();
+            let mut serial = BitWorld::from(&ws);
+            let mut parallel = serial.clone();
+            for _ in 0..20 {
+                serial.next();
+                parallel.next_parallel(threads);
+                assert_eq!(WorldState::from(&parallel), WorldState::from(&serial));
+            }
+        }
+    }
+
+    #[test]
+    fn test_same_evolution_for_examples() {
+        assert_same_evolution(WorldState::example1(), 10);
+        assert_same_evolution(WorldState::example2(), 200);
+        assert_same_evolution(WorldState::example3(), 100);
+    }
+}
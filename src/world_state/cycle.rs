@@ -0,0 +1,242 @@
+use super::{CellState, WorldState};
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// How the cells of a world end up repeating themselves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// Every cell is **Dead** from this generation on.
+    Extinct {
+        /// The first generation without any cell.
+        generation: u64,
+    },
+
+    /// From the generation `transient` on, the cells come back every
+    /// `period` generations, moved by `dx` columns and `dy` rows.
+    Cycle {
+        /// The number of generations before the cycle starts.
+        transient: u64,
+        /// The number of generations of the cycle.
+        period: u64,
+        /// The columns crossed by the cells during each period.
+        dx: i64,
+        /// The rows crossed by the cells during each period.
+        dy: i64,
+    },
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Outcome::Extinct { generation } => write!(f, "extinct at generation {generation}"),
+            Outcome::Cycle {
+                transient,
+                period,
+                dx,
+                dy,
+            } => {
+                write!(f, "cycle of period {period} from generation {transient}")?;
+                if (dx, dy) != (0, 0) {
+                    write!(f, ", moving by ({dx}, {dy}) each period")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The cells of a world which aren't **Dead**, relative to the top left
+/// corner of the rectangle around them.
+struct Shape {
+    /// The column of the corner in the world.
+    x: usize,
+
+    /// The row of the corner in the world.
+    y: usize,
+
+    /// The cells, row by row.
+    cells: Vec<(usize, usize, CellState)>,
+}
+
+impl Shape {
+    /// Return the hash of the cells, the same wherever they are in the world.
+    fn hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.cells.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl WorldState {
+    /// Return the cells of the world which aren't **Dead**, and where they are.
+    fn shape(&self) -> Shape {
+        let cells: Vec<_> = self
+            .iter()
+            .filter(|&(_, _, cell)| cell != CellState::Dead)
+            .collect();
+        let x = cells.iter().map(|&(x, _, _)| x).min().unwrap_or(0);
+        let y = cells.iter().map(|&(_, y, _)| y).min().unwrap_or(0);
+        Shape {
+            x,
+            y,
+            cells: cells
+                .into_iter()
+                .map(|(cx, cy, cell)| (cx - x, cy - y, cell))
+                .collect(),
+        }
+    }
+
+    /// Run a copy of the world until its cells repeat themselves or die,
+    /// and return how it ended, or `None` if it didn't within
+    /// `max_generations` generations.
+    ///
+    /// The cells are compared wherever they are, so that a spaceship is
+    /// found as a cycle moving by its displacement (in a world with edges,
+    /// before it reaches them). The generations are told apart by a hash of
+    /// their cells, the cells of the generations sharing a hash being kept
+    /// to be compared exactly.
+    pub fn find_cycle(&self, max_generations: u64) -> Option<Outcome> {
+        self.find_cycle_by(max_generations, Shape::hash)
+    }
+
+    /// Find the cycle of the world as `find_cycle()` does, the generations
+    /// being told apart by `hash`.
+    fn find_cycle_by(&self, max_generations: u64, hash: impl Fn(&Shape) -> u64) -> Option<Outcome> {
+        let mut ws = self.clone();
+        // the generations seen for each hash, with their cells.
+        let mut seen: HashMap<u64, Vec<(u64, Shape)>> = HashMap::new();
+        for generation in 0..=max_generations {
+            let shape = ws.shape();
+            if shape.cells.is_empty() {
+                return Some(Outcome::Extinct { generation });
+            }
+
+            let earlier = seen.entry(hash(&shape)).or_default();
+            if let Some((transient, first)) = earlier.iter().find(|(_, e)| e.cells == shape.cells) {
+                return Some(Outcome::Cycle {
+                    transient: *transient,
+                    period: generation - transient,
+                    dx: shape.x as i64 - first.x as i64,
+                    dy: shape.y as i64 - first.y as i64,
+                });
+            }
+            earlier.push((generation, shape));
+            ws.next();
+        }
+        None
+    }
+
+    /// Advance the world to the generation at which it died or its cycle
+    /// started, as found by `find_cycle()`, or by `max_generations`
+    /// generations if it didn't settle, and return how it ended.
+    pub fn settle(&mut self, max_generations: u64) -> Option<Outcome> {
+        let outcome = self.find_cycle(max_generations);
+        let generations = match outcome {
+            Some(Outcome::Extinct { generation }) => generation,
+            Some(Outcome::Cycle { transient, .. }) => transient,
+            None => max_generations,
+        };
+        for _ in 0..generations {
+            self.next();
+        }
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::pattern::Pattern;
+    use crate::world_state::Spawn;
+
+    fn cycle(transient: u64, period: u64, dx: i64, dy: i64) -> Option<Outcome> {
+        Some(Outcome::Cycle {
+            transient,
+            period,
+            dx,
+            dy,
+        })
+    }
+
+    #[test]
+    fn test_oscillator() {
+        let mut ws = WorldState::new(4);
+        ws.spawn_osc3(0, 0).unwrap();
+        assert_eq!(ws.find_cycle(10), cycle(0, 3, 0, 0));
+        assert_eq!(ws.find_cycle(2), None);
+    }
+
+    #[test]
+    fn test_spaceships() {
+        let mut ws = WorldState::new(20);
+        ws.spawn_glider4_downward(5, 2).unwrap();
+        assert_eq!(ws.find_cycle(10), cycle(0, 1, 0, 1));
+
+        let mut ws = WorldState::new(20);
+        ws.stamp(&Pattern::named("ship8").unwrap(), 10, 10).unwrap();
+        assert_eq!(ws.find_cycle(10), cycle(0, 1, -1, 0));
+    }
+
+    #[test]
+    fn test_transient() {
+        // the lone cell dies after 2 generations, leaving the oscillator.
+        let mut ws = WorldState::new(10);
+        ws.spawn_osc3(6, 6).unwrap();
+        ws.spawn_cell(0, 0, CellState::Alive).unwrap();
+        assert_eq!(ws.find_cycle(10), cycle(2, 3, 0, 0));
+    }
+
+    #[test]
+    fn test_colliding_hashes() {
+        // every generation has the same hash, only the cells telling them
+        // apart.
+        let mut ws = WorldState::new(10);
+        ws.spawn_osc3(6, 6).unwrap();
+        ws.spawn_cell(0, 0, CellState::Alive).unwrap();
+        assert_eq!(ws.find_cycle_by(10, |_| 0), cycle(2, 3, 0, 0));
+
+        let mut ws = WorldState::new(20);
+        ws.spawn_glider4_downward(5, 2).unwrap();
+        assert_eq!(ws.find_cycle_by(10, |_| 0), cycle(0, 1, 0, 1));
+    }
+
+    #[test]
+    fn test_extinction() {
+        let mut ws = WorldState::new(5);
+        assert_eq!(ws.find_cycle(10), Some(Outcome::Extinct { generation: 0 }));
+        ws.spawn_cell(2, 2, CellState::Alive).unwrap();
+        assert_eq!(ws.find_cycle(10), Some(Outcome::Extinct { generation: 2 }));
+    }
+
+    #[test]
+    fn test_settle() {
+        let mut ws = WorldState::new(10);
+        ws.spawn_osc3(6, 6).unwrap();
+        ws.spawn_cell(0, 0, CellState::Alive).unwrap();
+        let mut expected = ws.clone();
+        expected.next();
+        expected.next();
+        assert_eq!(ws.settle(10), cycle(2, 3, 0, 0));
+        assert_eq!(ws, expected);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            Outcome::Extinct { generation: 4 }.to_string(),
+            "extinct at generation 4"
+        );
+        assert_eq!(
+            cycle(2, 3, 0, 0).unwrap().to_string(),
+            "cycle of period 3 from generation 2"
+        );
+        assert_eq!(
+            cycle(0, 1, -1, 0).unwrap().to_string(),
+            "cycle of period 1 from generation 0, moving by (-1, 0) each period"
+        );
+    }
+}
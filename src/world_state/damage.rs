@@ -0,0 +1,292 @@
+use super::{
+    cell_color, cells_as_vertices, Automaton, CellShape, CellState, OutOfBounds, WorldState,
+};
+use crate::vertex::Vertex;
+
+use std::fmt;
+
+/// The color used to represent on a GUI the cells which differ between
+/// the two copies of a **Damaged** world.
+/// The content is an array representing the RGB values.
+const DAMAGE_COLOR: [f32; 3] = [1.0, 1.0, 0.0];
+
+/// Two copies of a world run side by side, the second one starting with
+/// a single cell flipped: a **Dead** cell made **Alive**, or another cell
+/// made **Dead**. How far they drift apart measures how chaotic the rule is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Damaged {
+    /// The world as it was given.
+    original: WorldState,
+
+    /// The copy of the world with the flipped cell.
+    damaged: WorldState,
+
+    /// The number of generations run since the cell was flipped.
+    generation: u64,
+}
+
+/// How much the two copies of a **Damaged** world differ at a generation.
+///
+/// The bounding box is the one of the cells which differ, and is `None`
+/// when the copies are the same.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Spread {
+    /// The number of generations run since the cell was flipped.
+    pub generation: u64,
+
+    /// The number of cells which differ (the Hamming distance).
+    pub distance: usize,
+
+    /// The leftmost column of the cells which differ.
+    pub min_x: Option<usize>,
+
+    /// The topmost row of the cells which differ.
+    pub min_y: Option<usize>,
+
+    /// The rightmost column of the cells which differ.
+    pub max_x: Option<usize>,
+
+    /// The bottommost row of the cells which differ.
+    pub max_y: Option<usize>,
+}
+
+impl Spread {
+    /// The line naming the columns of `Spread::to_csv()`.
+    pub const CSV_HEADER: &'static str = "generation,distance,min_x,min_y,max_x,max_y";
+
+    /// Write the spread as comma-separated values, the measures which are
+    /// `None` being left empty.
+    pub fn to_csv(&self) -> String {
+        let field = |value: Option<usize>| value.map(|v| v.to_string()).unwrap_or_default();
+        format!(
+            "{},{},{},{},{},{}",
+            self.generation,
+            self.distance,
+            field(self.min_x),
+            field(self.min_y),
+            field(self.max_x),
+            field(self.max_y)
+        )
+    }
+}
+
+impl Damaged {
+    /// Return the two copies of `ws`, the cell at column `x` and row `y`
+    /// being flipped in the second one, or an error if the cell is outside
+    /// the world.
+    pub fn new(ws: &WorldState, x: usize, y: usize) -> Result<Damaged, OutOfBounds> {
+        let mut damaged = ws.clone();
+        let flipped = match ws.get(x, y)? {
+            CellState::Dead => CellState::Alive,
+            _ => CellState::Dead,
+        };
+        damaged.set(x, y, flipped)?;
+        Ok(Damaged {
+            original: ws.clone(),
+            damaged,
+            generation: 0,
+        })
+    }
+
+    /// Return the world as it was given, at the current generation.
+    pub fn original(&self) -> &WorldState {
+        &self.original
+    }
+
+    /// Return the copy of the world with the flipped cell, at the current
+    /// generation.
+    pub fn damaged(&self) -> &WorldState {
+        &self.damaged
+    }
+
+    /// Advance both copies to their next state.
+    pub fn next(&mut self) {
+        self.original.next();
+        self.damaged.next();
+        self.generation += 1;
+    }
+
+    /// Return the cells which differ between the copies, row by row, as
+    /// (`x`, `y`).
+    pub fn differences(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.original
+            .iter()
+            .zip(self.damaged.iter())
+            .filter(|((_, _, a), (_, _, b))| a != b)
+            .map(|((x, y, _), _)| (x, y))
+    }
+
+    /// Return how much the copies differ at the current generation.
+    pub fn spread(&self) -> Spread {
+        let mut spread = Spread {
+            generation: self.generation,
+            distance: 0,
+            min_x: None,
+            min_y: None,
+            max_x: None,
+            max_y: None,
+        };
+        for (x, y) in self.differences() {
+            spread.distance += 1;
+            spread.min_x = Some(spread.min_x.map_or(x, |m| m.min(x)));
+            spread.min_y = Some(spread.min_y.map_or(y, |m| m.min(y)));
+            spread.max_x = Some(spread.max_x.map_or(x, |m| m.max(x)));
+            spread.max_y = Some(spread.max_y.map_or(y, |m| m.max(y)));
+        }
+        spread
+    }
+}
+
+/// Run `ws` and a copy of it in which the cell at column `x` and row `y` is
+/// flipped, as `Damaged` does, and return how much they differ at each
+/// generation from 0 to `generations`, or an error if the cell is outside
+/// the world.
+pub fn damage_spreading(
+    ws: &WorldState,
+    x: usize,
+    y: usize,
+    generations: u64,
+) -> Result<Vec<Spread>, OutOfBounds> {
+    let mut damaged = Damaged::new(ws, x, y)?;
+    let mut spreads = vec![damaged.spread()];
+    for _ in 0..generations {
+        damaged.next();
+        spreads.push(damaged.spread());
+    }
+    Ok(spreads)
+}
+
+impl fmt::Display for Damaged {
+    /// Write the copy with the flipped cell as `WorldState` does, the cells
+    /// which differ from the original world being shown in reverse video,
+    /// as `Diff::highlight()` does.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for ((x, _, a), (_, _, b)) in self.original.iter().zip(self.damaged.iter()) {
+            if a == b {
+                write!(f, "{b}")?;
+            } else {
+                write!(f, "\x1b[7m{b}\x1b[0m")?;
+            }
+            if x + 1 == self.damaged.width() {
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Automaton for Damaged {
+    fn step(&mut self, threads: usize) {
+        self.original.next_parallel(threads);
+        self.damaged.next_parallel(threads);
+        self.generation += 1;
+    }
+
+    /// The cells of the copy with the flipped cell are drawn, the cells
+    /// which differ from the original world being drawn in yellow.
+    fn as_vertices(&self) -> Vec<Vertex> {
+        let dying_stages = self.damaged.rule().dying_stages();
+        let cells = self.original.iter().zip(self.damaged.iter());
+        let cells = cells.filter_map(|((x, y, a), (_, _, b))| {
+            let color = if a == b {
+                cell_color(b, dying_stages)?
+            } else {
+                DAMAGE_COLOR
+            };
+            Some((x, y, color))
+        });
+        let shape = CellShape::of(self.damaged.neighbourhood(), 0);
+        cells_as_vertices(self.damaged.width(), self.damaged.height(), shape, cells)
+    }
+
+    /// Both copies have to be extinct.
+    fn is_extinct(&self) -> bool {
+        self.original.is_extinct() && self.damaged.is_extinct()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::world_state::Spawn;
+
+    #[test]
+    fn test_flipped_cell() {
+        let mut ws = WorldState::new(5);
+        ws.set(1, 1, CellState::Dying(0)).unwrap();
+        let damaged = Damaged::new(&ws, 1, 1).unwrap();
+        assert_eq!(damaged.damaged().get(1, 1), Ok(CellState::Dead));
+        assert_eq!(damaged.original(), &ws);
+        let damaged = Damaged::new(&ws, 3, 2).unwrap();
+        assert_eq!(damaged.damaged().get(3, 2), Ok(CellState::Alive));
+        assert_eq!(damaged.differences().collect::<Vec<_>>(), [(3, 2)]);
+        assert_eq!(Damaged::new(&ws, 5, 0), Err(OutOfBounds { x: 5, y: 0 }));
+    }
+
+    #[test]
+    fn test_damage_spreading() {
+        // the flipped cell, next to an alive cell, makes 4 cells be born.
+        let mut ws = WorldState::new(6);
+        ws.set(2, 2, CellState::Alive).unwrap();
+        let spreads = damage_spreading(&ws, 3, 2, 2).unwrap();
+        assert_eq!(spreads.len(), 3);
+        assert_eq!(
+            spreads[0],
+            Spread {
+                generation: 0,
+                distance: 1,
+                min_x: Some(3),
+                min_y: Some(2),
+                max_x: Some(3),
+                max_y: Some(2),
+            }
+        );
+        assert_eq!(spreads[1].distance, 5);
+        assert_eq!(
+            (
+                spreads[1].min_x,
+                spreads[1].min_y,
+                spreads[1].max_x,
+                spreads[1].max_y
+            ),
+            (Some(2), Some(1), Some(3), Some(3))
+        );
+        assert_eq!(spreads[1].to_csv(), "1,5,2,1,3,3");
+    }
+
+    #[test]
+    fn test_healed_damage() {
+        // a lone cell dies, whether it's there or not.
+        let ws = WorldState::new(5);
+        let spreads = damage_spreading(&ws, 2, 2, 3).unwrap();
+        let distances: Vec<_> = spreads.iter().map(|s| s.distance).collect();
+        assert_eq!(distances, [1, 1, 0, 0]);
+        assert_eq!(spreads[3].to_csv(), "3,0,,,,");
+
+        let mut damaged = Damaged::new(&ws, 2, 2).unwrap();
+        assert!(!damaged.is_extinct());
+        damaged.step(1);
+        damaged.step(1);
+        assert!(damaged.is_extinct());
+    }
+
+    #[test]
+    fn test_display() {
+        let mut ws = WorldState::new(4);
+        ws.spawn_osc3(0, 0).unwrap();
+        let damaged = Damaged::new(&ws, 0, 0).unwrap();
+        assert_eq!(
+            damaged.to_string(),
+            "\x1b[7mO\x1b[0mO..\n.XXO\nOXX.\n..O.\n"
+        );
+
+        // the states stay readable under the highlight.
+        let plain = damaged
+            .to_string()
+            .replace("\x1b[7m", "")
+            .replace("\x1b[0m", "");
+        assert_eq!(&plain.parse::<WorldState>().unwrap(), damaged.damaged());
+        assert_eq!(damaged.as_vertices().len(), 9 * 6);
+    }
+}
@@ -0,0 +1,292 @@
+use super::{CellState, WorldState};
+
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+
+/// A cell whose state differs between two worlds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Change {
+    /// The column of the cell.
+    pub x: usize,
+
+    /// The row of the cell.
+    pub y: usize,
+
+    /// The state of the cell in the first world.
+    pub old: CellState,
+
+    /// The state of the cell in the second world.
+    pub new: CellState,
+}
+
+/// The cells which differ between two worlds of the same dimensions, as
+/// returned by `WorldState::diff()`, which turn the first world into the
+/// second one with `WorldState::apply_diff()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diff {
+    /// The number of cells on each row of the worlds.
+    pub width: usize,
+
+    /// The number of rows of the worlds.
+    pub height: usize,
+
+    /// The cells which differ, row by row.
+    pub changes: Vec<Change>,
+}
+
+/// The reasons why two worlds can't be compared, or a **Diff** applied.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffError {
+    /// The worlds don't have the same dimensions.
+    Dimensions {
+        /// The dimensions of the first world, or of the diff.
+        expected: (usize, usize),
+        /// The dimensions of the second world, or of the world to which
+        /// the diff is applied.
+        found: (usize, usize),
+    },
+
+    /// A cell of the world isn't in the state the diff changes.
+    Conflict {
+        /// The change which can't be applied.
+        change: Change,
+        /// The state of the cell in the world.
+        found: CellState,
+    },
+
+    /// A changed cell lies outside of the dimensions of the diff.
+    OutOfBounds(Change),
+}
+
+impl fmt::Display for DiffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiffError::Dimensions { expected, found } => write!(
+                f,
+                "a world of {}x{} cells can't be compared with a world of {}x{} cells",
+                expected.0, expected.1, found.0, found.1
+            ),
+            DiffError::Conflict { change, found } => write!(
+                f,
+                "the cell ({}, {}) is `{found}` instead of `{}`",
+                change.x, change.y, change.old
+            ),
+            DiffError::OutOfBounds(change) => write!(
+                f,
+                "the cell ({}, {}) is outside of the world",
+                change.x, change.y
+            ),
+        }
+    }
+}
+
+impl Error for DiffError {}
+
+impl fmt::Display for Diff {
+    /// Write a line for each change, such as `(4, 2): O -> X`, the states
+    /// being shown as `CellState`'s `Display` does.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for Change { x, y, old, new } in &self.changes {
+            writeln!(f, "({x}, {y}): {old} -> {new}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Diff {
+    /// Return the grid of `ws` as `WorldState`'s `Display` writes it, the
+    /// changed cells being highlighted for a terminal (in reverse video).
+    ///
+    /// `ws` is usually the second world of the diff, and has its dimensions.
+    /// The changes can be in any order.
+    pub fn highlight<'a>(&'a self, ws: &'a WorldState) -> Highlight<'a> {
+        Highlight { diff: self, ws }
+    }
+}
+
+/// The grid of a world with the cells of a **Diff** highlighted, as
+/// returned by `Diff::highlight()`.
+pub struct Highlight<'a> {
+    /// The cells to highlight.
+    diff: &'a Diff,
+
+    /// The world written.
+    ws: &'a WorldState,
+}
+
+impl fmt::Display for Highlight<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let changed: HashSet<(usize, usize)> =
+            self.diff.changes.iter().map(|c| (c.x, c.y)).collect();
+        for (x, y, cell) in self.ws.iter() {
+            if changed.contains(&(x, y)) {
+                write!(f, "\x1b[7m{cell}\x1b[0m")?;
+            } else {
+                write!(f, "{cell}")?;
+            }
+            if x + 1 == self.ws.width() {
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl WorldState {
+    /// Return the cells which differ between the world and `other`, with
+    /// their state in each of them, or an error if the worlds don't have
+    /// the same dimensions.
+    ///
+    /// Only the cells are compared, not the rules or the topologies.
+    pub fn diff(&self, other: &WorldState) -> Result<Diff, DiffError> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return Err(DiffError::Dimensions {
+                expected: (self.width, self.height),
+                found: (other.width, other.height),
+            });
+        }
+        let changes = self
+            .iter()
+            .zip(other.world.iter())
+            .filter(|&((_, _, old), &new)| old != new)
+            .map(|((x, y, old), &new)| Change { x, y, old, new })
+            .collect();
+        Ok(Diff {
+            width: self.width,
+            height: self.height,
+            changes,
+        })
+    }
+
+    /// Set the cells changed by `diff` to their new state, replaying it.
+    ///
+    /// An error is returned, and the world left untouched, if the world
+    /// doesn't have the dimensions of the diff, or if a changed cell is
+    /// outside of them or isn't in its old state.
+    pub fn apply_diff(&mut self, diff: &Diff) -> Result<(), DiffError> {
+        if (self.width, self.height) != (diff.width, diff.height) {
+            return Err(DiffError::Dimensions {
+                expected: (diff.width, diff.height),
+                found: (self.width, self.height),
+            });
+        }
+        // every change is checked before the first one is applied.
+        for &change in &diff.changes {
+            let found = self
+                .get(change.x, change.y)
+                .map_err(|_| DiffError::OutOfBounds(change))?;
+            if found != change.old {
+                return Err(DiffError::Conflict { change, found });
+            }
+        }
+        for change in &diff.changes {
+            self.set(change.x, change.y, change.new)
+                .expect("the changes are in the world");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_diff_between_generations() {
+        let before: WorldState = "....\n.OO.\n....\n".parse().unwrap();
+        let mut after = before.clone();
+        after.next();
+        let diff = before.diff(&after).unwrap();
+        assert_eq!(diff.changes.len(), 6);
+        assert_eq!(
+            diff.changes[0],
+            Change {
+                x: 1,
+                y: 0,
+                old: CellState::Dead,
+                new: CellState::Alive,
+            }
+        );
+        assert_eq!(diff.to_string().lines().nth(2), Some("(1, 1): O -> X"));
+        assert!(before.diff(&before).unwrap().changes.is_empty());
+    }
+
+    #[test]
+    fn test_apply_diff() {
+        let mut ws = WorldState::example2();
+        let mut other = WorldState::with_dimensions(ws.width(), ws.height());
+        other.set_rule("B2/S/C5".parse().unwrap());
+        other.randomize_with_seed(0.3, 7).unwrap();
+        other.set(0, 0, CellState::Dying(2)).unwrap();
+        let diff = ws.diff(&other).unwrap();
+        ws.apply_diff(&diff).unwrap();
+        assert_eq!(ws.diff(&other).unwrap().changes, []);
+
+        // the diff can't be applied twice.
+        let conflict = ws.apply_diff(&diff).unwrap_err();
+        assert!(matches!(conflict, DiffError::Conflict { .. }));
+    }
+
+    #[test]
+    fn test_dimensions() {
+        let mut ws = WorldState::with_dimensions(4, 3);
+        let other = WorldState::with_dimensions(3, 4);
+        let error = DiffError::Dimensions {
+            expected: (4, 3),
+            found: (3, 4),
+        };
+        assert_eq!(ws.diff(&other), Err(error));
+        let diff = other.diff(&other).unwrap();
+        assert_eq!(
+            ws.apply_diff(&diff),
+            Err(DiffError::Dimensions {
+                expected: (3, 4),
+                found: (4, 3),
+            })
+        );
+    }
+
+    #[test]
+    fn test_apply_diff_out_of_bounds() {
+        let mut ws = WorldState::with_dimensions(4, 3);
+        let change = Change {
+            x: 4,
+            y: 0,
+            old: CellState::Dead,
+            new: CellState::Alive,
+        };
+        let diff = Diff {
+            width: 4,
+            height: 3,
+            changes: vec![change],
+        };
+        assert_eq!(ws.apply_diff(&diff), Err(DiffError::OutOfBounds(change)));
+        assert_eq!(ws, WorldState::with_dimensions(4, 3));
+    }
+
+    #[test]
+    fn test_highlight() {
+        let before: WorldState = "O..\n...\n".parse().unwrap();
+        let mut after = before.clone();
+        after.next();
+        let diff = before.diff(&after).unwrap();
+        assert_eq!(
+            diff.highlight(&after).to_string(),
+            "\x1b[7mX\x1b[0m..\n...\n"
+        );
+    }
+
+    #[test]
+    fn test_highlight_unordered_changes() {
+        let before: WorldState = "O..\n.O.\n".parse().unwrap();
+        let after: WorldState = "X..\n.X.\n".parse().unwrap();
+        let mut diff = before.diff(&after).unwrap();
+        diff.changes.reverse();
+        assert_eq!(
+            diff.highlight(&after).to_string(),
+            "\x1b[7mX\x1b[0m..\n.\x1b[7mX\x1b[0m.\n"
+        );
+    }
+}
@@ -0,0 +1,630 @@
+use super::sparse_world::{SparseWorld, Viewport};
+use super::{CellState, ConfigError, Topology, WorldState};
+use crate::neighbourhood::Neighbourhood;
+use crate::rule::Rule;
+
+use std::collections::HashMap;
+
+/// The index of a node in the arena of a **HashLife** universe.
+type NodeId = u32;
+
+/// The leaves of the quadtree, one for each state a cell can take.
+const DEAD: NodeId = 0;
+const ALIVE: NodeId = 1;
+const DYING: NodeId = 2;
+
+/// The largest number of generations a universe can jump over at once,
+/// so that the coordinates of its cells, which spread by up to a cell per
+/// generation, stay within an `i64`.
+pub const MAX_JUMP: u64 = (1 << 56) - 1;
+
+/// The number of nodes above which the nodes which aren't part of the
+/// universe anymore, and the remembered futures, are forgotten, even in
+/// the middle of a jump.
+const MAX_NODES: usize = 1 << 21;
+
+/// A square of 2^`level` x 2^`level` cells, split into four quadrants
+/// of the level below (in order: north-west, north-east, south-west
+/// and south-east). The leaves (level 0) have no children.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Node {
+    level: u8,
+    children: [NodeId; 4],
+}
+
+/// A memoised version of the Cellular Automaton, able to jump over a huge
+/// number of generations at once (the HashLife algorithm).
+///
+/// The world is a quadtree whose identical nodes are shared (hash-consing),
+/// and the future of every node is remembered once computed. Patterns with
+/// regularities in space and time are thus advanced in a time that grows
+/// much slower than the number of generations.
+///
+/// The universe is unbounded: the edges and topology of the **WorldState**
+/// it comes from are ignored while it runs.
+#[derive(Clone, Debug)]
+pub struct HashLife {
+    /// Every node ever created, the leaves first.
+    nodes: Vec<Node>,
+
+    /// The index of each node in `nodes`, so that a node is never duplicated.
+    ids: HashMap<Node, NodeId>,
+
+    /// The centre of a node advanced by 2^`j` generations, keyed by (node, `j`).
+    results: HashMap<(NodeId, u8), NodeId>,
+
+    /// The node with only dead cells for each level.
+    empty: Vec<NodeId>,
+
+    /// The node representing the whole universe.
+    root: NodeId,
+
+    /// The column and row of the top left cell of the root.
+    origin: (i64, i64),
+
+    /// The number of generations the universe went through.
+    generation: u64,
+
+    /// The width and height of the world the universe was created from,
+    /// used when it's converted back.
+    dimensions: (usize, usize),
+
+    /// The topology of the world the universe was created from, given back
+    /// to it when it's converted back.
+    topology: Topology,
+
+    /// The number of nodes above which they are forgotten, `MAX_NODES`
+    /// outside of the tests.
+    max_nodes: usize,
+}
+
+impl HashLife {
+    /// Create a universe with only dead cells, whose root covers
+    /// 2^`level` x 2^`level` cells from the origin.
+    fn new(level: u8, dimensions: (usize, usize), topology: Topology) -> HashLife {
+        let leaf = |_| Node {
+            level: 0,
+            children: [DEAD; 4],
+        };
+        let mut hl = HashLife {
+            nodes: (0..3).map(leaf).collect(),
+            ids: HashMap::new(),
+            results: HashMap::new(),
+            empty: vec![DEAD],
+            root: DEAD,
+            origin: (0, 0),
+            generation: 0,
+            dimensions,
+            topology,
+            max_nodes: MAX_NODES,
+        };
+        hl.root = hl.empty(level);
+        hl
+    }
+
+    /// Return the number of generations the universe went through.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Return the unique node having the given `children`.
+    fn node(&mut self, children: [NodeId; 4]) -> NodeId {
+        let node = Node {
+            level: self.nodes[children[0] as usize].level + 1,
+            children,
+        };
+        if let Some(&id) = self.ids.get(&node) {
+            return id;
+        }
+        let id = self.nodes.len() as NodeId;
+        self.nodes.push(node);
+        self.ids.insert(node, id);
+        id
+    }
+
+    /// Return the node with only dead cells at `level`.
+    fn empty(&mut self, level: u8) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let below = *self.empty.last().unwrap();
+            let empty = self.node([below; 4]);
+            self.empty.push(empty);
+        }
+        self.empty[level as usize]
+    }
+
+    fn level(&self, id: NodeId) -> u8 {
+        self.nodes[id as usize].level
+    }
+
+    fn children(&self, id: NodeId) -> [NodeId; 4] {
+        self.nodes[id as usize].children
+    }
+
+    fn is_empty(&self, id: NodeId) -> bool {
+        self.empty.get(self.level(id) as usize) == Some(&id)
+    }
+
+    /// Return the node of level `level` whose top left cell is (`x`, `y`),
+    /// with the cell states given by `get`.
+    fn build(&mut self, x: i64, y: i64, level: u8, get: &impl Fn(i64, i64) -> CellState) -> NodeId {
+        if level == 0 {
+            return match get(x, y) {
+                CellState::Alive => ALIVE,
+                CellState::Dying(_) => DYING,
+                CellState::Dead => DEAD,
+            };
+        }
+        let half = 1 << (level - 1);
+        let children = [
+            self.build(x, y, level - 1, get),
+            self.build(x + half, y, level - 1, get),
+            self.build(x, y + half, level - 1, get),
+            self.build(x + half, y + half, level - 1, get),
+        ];
+        self.node(children)
+    }
+
+    /// Call `f` with the column, row and state of every cell which isn't
+    /// dead in the node `id`, whose top left cell is (`x`, `y`).
+    fn for_each_cell(&self, id: NodeId, x: i64, y: i64, f: &mut impl FnMut(i64, i64, CellState)) {
+        if self.is_empty(id) {
+            return;
+        }
+        match id {
+            ALIVE => f(x, y, CellState::Alive),
+            DYING => f(x, y, CellState::Dying(0)),
+            _ => {
+                let half = 1 << (self.level(id) - 1);
+                let [nw, ne, sw, se] = self.children(id);
+                self.for_each_cell(nw, x, y, f);
+                self.for_each_cell(ne, x + half, y, f);
+                self.for_each_cell(sw, x, y + half, f);
+                self.for_each_cell(se, x + half, y + half, f);
+            }
+        }
+    }
+
+    /// Return the node made of the centre of the node `id`, one level below.
+    fn centre(&mut self, id: NodeId) -> NodeId {
+        let [nw, ne, sw, se] = self.children(id);
+        let children = [
+            self.children(nw)[3],
+            self.children(ne)[2],
+            self.children(sw)[1],
+            self.children(se)[0],
+        ];
+        self.node(children)
+    }
+
+    /// Surround the root with dead cells, doubling its size while keeping
+    /// it centred on the same cells.
+    fn expand(&mut self) {
+        let level = self.level(self.root);
+        let empty = self.empty(level - 1);
+        let [nw, ne, sw, se] = self.children(self.root);
+        let children = [
+            self.node([empty, empty, empty, nw]),
+            self.node([empty, empty, ne, empty]),
+            self.node([empty, sw, empty, empty]),
+            self.node([se, empty, empty, empty]),
+        ];
+        self.root = self.node(children);
+
+        let quarter = 1 << (level - 1);
+        self.origin = (self.origin.0 - quarter, self.origin.1 - quarter);
+    }
+
+    /// Return `true` if every cell of the root which isn't dead lies in its
+    /// central square, whose sides are half the ones of the root.
+    fn is_padded(&self) -> bool {
+        let [nw, ne, sw, se] = self.children(self.root);
+        let [nw, ne, sw, se] = [
+            self.children(nw),
+            self.children(ne),
+            self.children(sw),
+            self.children(se),
+        ];
+        [
+            nw[0], nw[1], nw[2], ne[0], ne[1], ne[3], sw[0], sw[2], sw[3], se[1], se[2], se[3],
+        ]
+        .iter()
+        .all(|&id| self.is_empty(id))
+    }
+
+    /// Return the 2x2 centre of the 4x4 node `id` after one generation.
+    fn base_result(&mut self, id: NodeId) -> NodeId {
+        let mut cells = [[DEAD; 4]; 4];
+        let [nw, ne, sw, se] = self.children(id);
+        for (q, quadrant) in [nw, ne, sw, se].into_iter().enumerate() {
+            for (c, cell) in self.children(quadrant).into_iter().enumerate() {
+                let x = (q % 2) * 2 + c % 2;
+                let y = (q / 2) * 2 + c / 2;
+                cells[y][x] = cell;
+            }
+        }
+
+        let next = |x: usize, y: usize| match cells[y][x] {
+            ALIVE => DYING,
+            DYING => DEAD,
+            _ => {
+                let alives = [
+                    cells[y - 1][x - 1],
+                    cells[y - 1][x],
+                    cells[y - 1][x + 1],
+                    cells[y][x - 1],
+                    cells[y][x + 1],
+                    cells[y + 1][x - 1],
+                    cells[y + 1][x],
+                    cells[y + 1][x + 1],
+                ]
+                .iter()
+                .filter(|&&cell| cell == ALIVE)
+                .count();
+
+                match alives {
+                    2 => ALIVE,
+                    _ => DEAD,
+                }
+            }
+        };
+        let children = [next(1, 1), next(2, 1), next(1, 2), next(2, 2)];
+        self.node(children)
+    }
+
+    /// Return the centre of the node `id` (one level below) advanced by
+    /// 2^`j` generations. `j` can't exceed the level of the node minus 2.
+    ///
+    /// `None` is returned if the universe had more than `cap` nodes on the
+    /// way, the nodes created so far being kept.
+    fn result(&mut self, id: NodeId, j: u8, cap: usize) -> Option<NodeId> {
+        if let Some(&result) = self.results.get(&(id, j)) {
+            return Some(result);
+        }
+        if self.nodes.len() > cap {
+            return None;
+        }
+
+        let level = self.level(id);
+        let result = if self.is_empty(id) {
+            self.empty(level - 1)
+        } else if level == 2 {
+            self.base_result(id)
+        } else {
+            // the 9 overlapping sub-squares of the node, one level below.
+            let [nw, ne, sw, se] = self.children(id);
+            let [nw_, ne_, sw_, se_] = [
+                self.children(nw),
+                self.children(ne),
+                self.children(sw),
+                self.children(se),
+            ];
+            let squares = [
+                nw,
+                self.node([nw_[1], ne_[0], nw_[3], ne_[2]]),
+                ne,
+                self.node([nw_[2], nw_[3], sw_[0], sw_[1]]),
+                self.node([nw_[3], ne_[2], sw_[1], se_[0]]),
+                self.node([ne_[2], ne_[3], se_[0], se_[1]]),
+                sw,
+                self.node([sw_[1], se_[0], sw_[3], se_[2]]),
+                se,
+            ];
+
+            // at full speed, the first half of the generations is done on
+            // the 9 sub-squares, otherwise only their centre is kept.
+            let full_speed = j == level - 2;
+            let mut r = [DEAD; 9];
+            for (i, &square) in squares.iter().enumerate() {
+                r[i] = if full_speed {
+                    self.result(square, j - 1, cap)?
+                } else {
+                    self.centre(square)
+                };
+            }
+
+            let j = if full_speed { j - 1 } else { j };
+            let quadrants = [
+                self.node([r[0], r[1], r[3], r[4]]),
+                self.node([r[1], r[2], r[4], r[5]]),
+                self.node([r[3], r[4], r[6], r[7]]),
+                self.node([r[4], r[5], r[7], r[8]]),
+            ];
+            let children = [
+                self.result(quadrants[0], j, cap)?,
+                self.result(quadrants[1], j, cap)?,
+                self.result(quadrants[2], j, cap)?,
+                self.result(quadrants[3], j, cap)?,
+            ];
+            self.node(children)
+        };
+
+        self.results.insert((id, j), result);
+        Some(result)
+    }
+
+    /// Copy the node `id` of `other`, and the nodes below it, into the
+    /// universe. `copied` maps the nodes of `other` already copied to
+    /// their copy.
+    fn copy(
+        &mut self,
+        other: &HashLife,
+        id: NodeId,
+        copied: &mut HashMap<NodeId, NodeId>,
+    ) -> NodeId {
+        if let Some(&copy) = copied.get(&id) {
+            return copy;
+        }
+        let children = other
+            .children(id)
+            .map(|child| self.copy(other, child, copied));
+        let copy = self.node(children);
+        copied.insert(id, copy);
+        copy
+    }
+
+    /// Forget the nodes which aren't part of the root anymore, and the
+    /// futures of the nodes, which are computed again when needed.
+    fn collect_garbage(&mut self) {
+        let mut hl = HashLife::new(0, self.dimensions, self.topology);
+        hl.max_nodes = self.max_nodes;
+        // the empty nodes are known as such before they are copied.
+        hl.empty(self.level(self.root));
+        let mut copied = HashMap::from([(DEAD, DEAD), (ALIVE, ALIVE), (DYING, DYING)]);
+        hl.root = hl.copy(self, self.root, &mut copied);
+        hl.origin = self.origin;
+        hl.generation = self.generation;
+        *self = hl;
+    }
+
+    /// Advance the universe by 2^`j` generations at once, or by two jumps
+    /// of 2^(`j` - 1) generations if the nodes don't fit under `MAX_NODES`.
+    ///
+    /// `j` must leave the jumps, in total, below `MAX_JUMP` generations.
+    pub fn step_pow2(&mut self, j: u8) {
+        if self.nodes.len() > self.max_nodes {
+            self.collect_garbage();
+        }
+        if self.advance(j, self.max_nodes) {
+            return;
+        }
+        // the nodes went past the cap on the way: start again from the
+        // nodes of the root alone, a single generation being allowed to
+        // go past it.
+        self.collect_garbage();
+        let cap = if j == 0 { usize::MAX } else { self.max_nodes };
+        if !self.advance(j, cap) {
+            self.step_pow2(j - 1);
+            self.step_pow2(j - 1);
+        }
+    }
+
+    /// Advance the universe by 2^`j` generations at once, and return
+    /// `true`, unless it had more than `cap` nodes on the way, in which case
+    /// it's left as it was.
+    fn advance(&mut self, j: u8, cap: usize) -> bool {
+        let (root, origin) = (self.root, self.origin);
+        while self.level(self.root) < j + 2 || !self.is_padded() {
+            self.expand();
+        }
+        // leave enough room around the cells for them to spread.
+        self.expand();
+
+        let level = self.level(self.root);
+        let Some(result) = self.result(self.root, j, cap) else {
+            (self.root, self.origin) = (root, origin);
+            return false;
+        };
+        self.root = result;
+        let quarter = 1 << (level - 2);
+        self.origin = (self.origin.0 + quarter, self.origin.1 + quarter);
+        self.generation += 1 << j;
+        true
+    }
+    /// Advance the universe by `generations` generations, as a sum of
+    /// jumps of a power of two.
+    ///
+    /// An error is returned, and the universe left untouched, if the
+    /// universe would have gone through more than `MAX_JUMP` generations.
+    pub fn jump(&mut self, generations: u64) -> Result<(), ConfigError> {
+        match self.generation.checked_add(generations) {
+            Some(total) if total <= MAX_JUMP => {}
+            _ => return Err(ConfigError::JumpLength(generations)),
+        }
+        for j in 0..u64::BITS as u8 {
+            if generations & (1 << j) != 0 {
+                self.step_pow2(j);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Build the universe from the cells of a **WorldState**, placed with
+/// their top left cell at the origin.
+///
+/// # Panics
+///
+/// Panics if the **WorldState** doesn't follow the rule of Brian's Brain
+/// in the Moore neighbourhood,
+/// the only one **HashLife** computes.
+impl From<&WorldState> for HashLife {
+    fn from(ws: &WorldState) -> HashLife {
+        assert!(
+            ws.rule() == &Rule::default() && ws.neighbourhood() == &Neighbourhood::default(),
+            "HashLife only follows the rule and the neighbourhood of Brian's Brain"
+        );
+        let side = ws.width.max(ws.height).max(2).next_power_of_two();
+        let level = side.trailing_zeros() as u8;
+
+        let mut hl = HashLife::new(level, (ws.width, ws.height), ws.topology);
+        let get = |x: i64, y: i64| match (x as usize, y as usize) {
+            (x, y) if x < ws.width && y < ws.height => ws.world[y * ws.width + x],
+            _ => CellState::Dead,
+        };
+        hl.root = hl.build(0, 0, level, &get);
+        hl
+    }
+}
+
+/// Keep the cells of the universe which lie in the rectangle of the
+/// **WorldState** it was built from, which gets its topology back.
+impl From<&HashLife> for WorldState {
+    fn from(hl: &HashLife) -> WorldState {
+        let (width, height) = hl.dimensions;
+        let mut ws = WorldState::with_dimensions(width, height);
+        ws.set_topology(hl.topology);
+        hl.for_each_cell(hl.root, hl.origin.0, hl.origin.1, &mut |x, y, state| {
+            if (0..width as i64).contains(&x) && (0..height as i64).contains(&y) {
+                ws.world[y as usize * width + x as usize] = state;
+            }
+        });
+        ws
+    }
+}
+
+/// Keep every cell of the universe, using the rectangle of the
+/// **WorldState** it was built from as viewport.
+impl From<&HashLife> for SparseWorld {
+    fn from(hl: &HashLife) -> SparseWorld {
+        let (width, height) = hl.dimensions;
+        let mut sw = SparseWorld::new(Viewport {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        });
+        hl.for_each_cell(hl.root, hl.origin.0, hl.origin.1, &mut |x, y, state| {
+            sw.set(x, y, state);
+        });
+        sw
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::world_state::Spawn;
+
+    /// Check that jumping over `generations` generations gives the same
+    /// universe as running a **SparseWorld** one generation at a time.
+    fn assert_same_as_sparse_world(ws: &WorldState, generations: u64) {
+        let mut hl = HashLife::from(ws);
+        let mut sw = SparseWorld::from(ws);
+        hl.jump(generations).unwrap();
+        for _ in 0..generations {
+            sw.next();
+        }
+        assert_eq!(hl.generation(), generations);
+        assert_eq!(SparseWorld::from(&hl), sw);
+    }
+
+    #[test]
+    fn test_conversion_round_trip() {
+        let mut ws = WorldState::with_dimensions(37, 20);
+        ws.randomize(0.4).unwrap();
+        ws.spawn_wick3(30, 10).unwrap();
+        assert_eq!(WorldState::from(&HashLife::from(&ws)), ws);
+    }
+
+    #[test]
+    fn test_conversion_keeps_the_topology() {
+        let mut ws = WorldState::new(10);
+        ws.set_topology(Topology::Torus);
+        ws.spawn_wick3(3, 3).unwrap();
+        let round_trip = WorldState::from(&HashLife::from(&ws));
+        assert_eq!(round_trip.topology(), Topology::Torus);
+        assert_eq!(round_trip, ws);
+    }
+
+    #[test]
+    fn test_step_pow2_same_as_sparse_world() {
+        let mut ws = WorldState::new(30);
+        ws.randomize(0.2).unwrap();
+        for j in 0..7 {
+            assert_same_as_sparse_world(&ws, 1 << j);
+        }
+    }
+
+    #[test]
+    fn test_jump_same_as_sparse_world_for_examples() {
+        assert_same_as_sparse_world(&WorldState::example1(), 47);
+        assert_same_as_sparse_world(&WorldState::example2(), 300);
+        assert_same_as_sparse_world(&WorldState::example3(), 123);
+    }
+
+    #[test]
+    fn test_jump_one_billion_generations() {
+        let mut ws = WorldState::new(8);
+        ws.spawn_glider4_downward(2, 2).unwrap();
+        let mut hl = HashLife::from(&ws);
+        hl.jump(1_000_000_000).unwrap();
+
+        let mut cells = vec![];
+        hl.for_each_cell(hl.root, hl.origin.0, hl.origin.1, &mut |x, y, state| {
+            cells.push((x, y, state));
+        });
+        cells.sort_by_key(|&(x, y, _)| (y, x));
+        assert_eq!(
+            cells,
+            vec![
+                (2, 1_000_000_002, CellState::Dying(0)),
+                (3, 1_000_000_002, CellState::Dying(0)),
+                (2, 1_000_000_003, CellState::Alive),
+                (3, 1_000_000_003, CellState::Alive),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_jump_too_far() {
+        let mut ws = WorldState::new(8);
+        ws.spawn_glider4_downward(2, 2).unwrap();
+        let mut hl = HashLife::from(&ws);
+        assert_eq!(hl.jump(u64::MAX), Err(ConfigError::JumpLength(u64::MAX)));
+        assert_eq!(hl.generation(), 0);
+        hl.jump(MAX_JUMP - 1).unwrap();
+        assert_eq!(hl.jump(2), Err(ConfigError::JumpLength(2)));
+        hl.jump(1).unwrap();
+        assert_eq!(hl.generation(), MAX_JUMP);
+        assert_eq!(SparseWorld::from(&hl).cells().count(), 4);
+    }
+
+    #[test]
+    fn test_collect_garbage() {
+        let mut ws = WorldState::new(30);
+        ws.randomize(0.2).unwrap();
+        let mut hl = HashLife::from(&ws);
+        hl.jump(100).unwrap();
+        let nodes = hl.nodes.len();
+        let sw = SparseWorld::from(&hl);
+        hl.collect_garbage();
+        assert!(hl.nodes.len() < nodes);
+        assert!(hl.results.is_empty());
+        assert_eq!(SparseWorld::from(&hl), sw);
+        assert_eq!(hl.generation(), 100);
+
+        // the universe keeps running as it would have.
+        let mut sw = SparseWorld::from(&hl);
+        hl.jump(50).unwrap();
+        for _ in 0..50 {
+            sw.next();
+        }
+        assert_eq!(SparseWorld::from(&hl), sw);
+    }
+
+    #[test]
+    fn test_collect_garbage_during_a_jump() {
+        let mut ws = WorldState::new(30);
+        ws.randomize_with_seed(0.2, 7).unwrap();
+        let mut hl = HashLife::from(&ws);
+        let mut sw = SparseWorld::from(&ws);
+        hl.max_nodes = 5000;
+        hl.jump(100).unwrap();
+        for _ in 0..100 {
+            sw.next();
+        }
+        assert_eq!(hl.generation(), 100);
+        assert_eq!(SparseWorld::from(&hl), sw);
+    }
+}
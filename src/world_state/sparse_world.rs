@@ -0,0 +1,434 @@
+use super::{
+    cell_color, cells_as_vertices, Automaton, CellShape, CellState, OutOfBounds, Spawn, WorldState,
+};
+use crate::neighbourhood::Neighbourhood;
+use crate::rule::Rule;
+use crate::vertex::Vertex;
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// The number of cells on each side of a tile.
+const TILE_SIZE: i64 = 16;
+
+/// The cells of a square of `TILE_SIZE` x `TILE_SIZE` cells, row by row.
+type Tile = [CellState; (TILE_SIZE * TILE_SIZE) as usize];
+
+/// A tile with only dead cells.
+const EMPTY_TILE: Tile = [CellState::Dead; (TILE_SIZE * TILE_SIZE) as usize];
+
+/// The rectangle of a **SparseWorld** which is displayed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Viewport {
+    /// The column of the top left cell.
+    pub x: i64,
+
+    /// The row of the top left cell.
+    pub y: i64,
+
+    /// The number of cells on each row.
+    pub width: usize,
+
+    /// The number of rows.
+    pub height: usize,
+}
+
+/// An unbounded version of the Cellular Automaton, in which patterns can
+/// grow forever.
+///
+/// Only the tiles containing cells that aren't **Dead** are stored, keyed
+/// by their position, so the memory used depends on the pattern and not
+/// on the space it covers. The coordinates of the cells can be negative.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SparseWorld {
+    /// The tiles containing at least one cell **Alive** or **Dying**,
+    /// keyed by the column and row of the tile.
+    tiles: HashMap<(i64, i64), Tile>,
+
+    /// The part of the world used for the display.
+    viewport: Viewport,
+
+    /// How the cells evolve from one generation to the next.
+    rule: Rule,
+
+    /// The cells counted as neighbours by the rule.
+    neighbourhood: Neighbourhood,
+}
+
+impl fmt::Display for SparseWorld {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Viewport {
+            x,
+            y,
+            width,
+            height,
+        } = self.viewport;
+
+        let mut s = String::with_capacity((width + 1) * height);
+        for row in y..y + height as i64 {
+            for column in x..x + width as i64 {
+                s.push_str(&self.get(column, row).to_string());
+            }
+            s.push('\n');
+        }
+        write!(f, "{s}")
+    }
+}
+
+impl SparseWorld {
+    /// Create a new **SparseWorld** with only dead cells, and the given
+    /// `viewport` used for the display.
+    pub fn new(viewport: Viewport) -> SparseWorld {
+        SparseWorld {
+            tiles: HashMap::new(),
+            viewport,
+            rule: Rule::default(),
+            neighbourhood: Neighbourhood::default(),
+        }
+    }
+
+    /// Change how the cells evolve from one generation to the next.
+    ///
+    /// Only the cells near the ones **Alive** are computed, so the births
+    /// of a rule with `B0` only happen there.
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+    }
+
+    /// Change the cells counted as neighbours by the rule.
+    pub fn set_neighbourhood(&mut self, neighbourhood: Neighbourhood) {
+        self.neighbourhood = neighbourhood;
+    }
+
+    /// Return the number of tiles, on each side of a tile, in which the
+    /// neighbours of its cells can be.
+    fn tile_reach(&self) -> i64 {
+        self.neighbourhood
+            .reach()
+            .div_ceil(TILE_SIZE as usize)
+            .max(1) as i64
+    }
+
+    /// Return the tile containing the cell (`x`, `y`), and the index of
+    /// the cell in that tile.
+    fn locate(x: i64, y: i64) -> ((i64, i64), usize) {
+        let tile = (x.div_euclid(TILE_SIZE), y.div_euclid(TILE_SIZE));
+        let i = y.rem_euclid(TILE_SIZE) * TILE_SIZE + x.rem_euclid(TILE_SIZE);
+        (tile, i as usize)
+    }
+
+    /// Return the state of the cell at column `x` and row `y`.
+    pub fn get(&self, x: i64, y: i64) -> CellState {
+        let (tile, i) = Self::locate(x, y);
+        self.tiles
+            .get(&tile)
+            .map_or(CellState::Dead, |cells| cells[i])
+    }
+
+    /// Change the state of the cell at column `x` and row `y`.
+    pub fn set(&mut self, x: i64, y: i64, state: CellState) {
+        let (tile, i) = Self::locate(x, y);
+        match state {
+            CellState::Dead => {
+                if let Some(cells) = self.tiles.get_mut(&tile) {
+                    cells[i] = state;
+                    if cells.iter().all(|&c| c == CellState::Dead) {
+                        self.tiles.remove(&tile);
+                    }
+                }
+            }
+            _ => self.tiles.entry(tile).or_insert(EMPTY_TILE)[i] = state,
+        }
+    }
+
+    /// Return the cells which aren't **Dead**, as (`x`, `y`, `state`),
+    /// in no particular order.
+    pub fn cells(&self) -> impl Iterator<Item = (i64, i64, CellState)> + '_ {
+        self.tiles.iter().flat_map(|(&(tx, ty), cells)| {
+            cells
+                .iter()
+                .enumerate()
+                .filter(|(_, &cell)| cell != CellState::Dead)
+                .map(move |(i, &cell)| {
+                    let (x, y) = (i as i64 % TILE_SIZE, i as i64 / TILE_SIZE);
+                    (tx * TILE_SIZE + x, ty * TILE_SIZE + y, cell)
+                })
+        })
+    }
+
+    /// Advance the world to its next state, following the same rules
+    /// as `WorldState::next()`.
+    ///
+    /// Besides the stored tiles, the tiles around the ones containing
+    /// cells **Alive**, as far as the neighbourhood reaches, are computed,
+    /// so that the pattern can grow.
+    pub fn next(&mut self) {
+        let tile_reach = self.tile_reach();
+        let mut candidates: HashSet<(i64, i64)> = self.tiles.keys().copied().collect();
+        for (&(tx, ty), cells) in &self.tiles {
+            if cells.contains(&CellState::Alive) {
+                for dy in -tile_reach..=tile_reach {
+                    for dx in -tile_reach..=tile_reach {
+                        candidates.insert((tx + dx, ty + dy));
+                    }
+                }
+            }
+        }
+
+        self.tiles = candidates
+            .into_iter()
+            .map(|tile| (tile, self.next_tile(tile)))
+            .filter(|(_, cells)| cells.iter().any(|&c| c != CellState::Dead))
+            .collect();
+    }
+
+    /// Return the next state of the tile at column `tx` and row `ty`.
+    fn next_tile(&self, (tx, ty): (i64, i64)) -> Tile {
+        // the cells alive in the tile and in the border of its neighbours,
+        // indexed from -reach to TILE_SIZE + reach - 1 (shifted by reach).
+        let reach = self.neighbourhood.reach() as i64;
+        let padded = TILE_SIZE + 2 * reach;
+        let mut alive = vec![false; (padded * padded) as usize];
+        let tile_reach = self.tile_reach();
+        for dy in -tile_reach..=tile_reach {
+            for dx in -tile_reach..=tile_reach {
+                let Some(cells) = self.tiles.get(&(tx + dx, ty + dy)) else {
+                    continue;
+                };
+                for (i, cell) in cells.iter().enumerate() {
+                    let px = dx * TILE_SIZE + i as i64 % TILE_SIZE + reach;
+                    let py = dy * TILE_SIZE + i as i64 / TILE_SIZE + reach;
+                    if (0..padded).contains(&px)
+                        && (0..padded).contains(&py)
+                        && *cell == CellState::Alive
+                    {
+                        alive[(py * padded + px) as usize] = true;
+                    }
+                }
+            }
+        }
+
+        // the tiles have an even size, so the rows of a tile have the
+        // same parity as the rows of the world.
+        let offsets = [
+            self.neighbourhood.offsets(false),
+            self.neighbourhood.offsets(true),
+        ];
+
+        let current = self.tiles.get(&(tx, ty));
+        let mut next = EMPTY_TILE;
+        for (i, cell) in next.iter_mut().enumerate() {
+            let state = current.map_or(CellState::Dead, |cells| cells[i]);
+            *cell = self.rule.next_state(state, || {
+                let (x, y) = (i as i64 % TILE_SIZE, i as i64 / TILE_SIZE);
+                offsets[y as usize % 2]
+                    .iter()
+                    .filter(|&&(dx, dy)| {
+                        let (px, py) = (x + reach + dx as i64, y + reach + dy as i64);
+                        alive[(py * padded + px) as usize]
+                    })
+                    .count()
+            });
+        }
+        next
+    }
+}
+
+impl Automaton for SparseWorld {
+    /// The tiles are computed on a single thread, `threads` is ignored.
+    fn step(&mut self, _threads: usize) {
+        self.next();
+    }
+
+    /// Only the cells inside the viewport are returned.
+    fn as_vertices(&self) -> Vec<Vertex> {
+        let Viewport {
+            x,
+            y,
+            width,
+            height,
+        } = self.viewport;
+
+        let dying_stages = self.rule.dying_stages();
+        let cells = (0..height).flat_map(|row| {
+            (0..width).filter_map(move |column| {
+                let state = self.get(x + column as i64, y + row as i64);
+                Some((column, row, cell_color(state, dying_stages)?))
+            })
+        });
+        let shape = CellShape::of(&self.neighbourhood, y);
+        cells_as_vertices(width, height, shape, cells)
+    }
+
+    /// The cells outside of the viewport are taken into account.
+    fn is_extinct(&self) -> bool {
+        self.tiles.is_empty()
+    }
+}
+
+impl Spawn for SparseWorld {
+    /// Return true, the world being unbounded.
+    fn contains(&self, _x: i64, _y: i64) -> bool {
+        true
+    }
+
+    fn spawn_cell(&mut self, x: i64, y: i64, state: CellState) -> Result<(), OutOfBounds> {
+        self.set(x, y, state);
+        Ok(())
+    }
+}
+
+/// Copy the cells, the rule and the neighbourhood of a **WorldState**, and
+/// use its whole area as viewport.
+/// The topology of the **WorldState** is lost, since the copy is unbounded.
+impl From<&WorldState> for SparseWorld {
+    fn from(ws: &WorldState) -> SparseWorld {
+        let mut sw = SparseWorld::new(Viewport {
+            x: 0,
+            y: 0,
+            width: ws.width,
+            height: ws.height,
+        });
+        sw.set_rule(ws.rule.clone());
+        sw.set_neighbourhood(ws.neighbourhood.clone());
+        for (i, &cell) in ws.world.iter().enumerate() {
+            if cell != CellState::Dead {
+                sw.set((i % ws.width) as i64, (i / ws.width) as i64, cell);
+            }
+        }
+        sw
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::world_state::ALIVE_COLOR;
+
+    fn viewport(x: i64, y: i64, width: usize, height: usize) -> Viewport {
+        Viewport {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn test_cells_are_the_ones_not_dead() {
+        let mut sw = SparseWorld::new(viewport(0, 0, 1, 1));
+        sw.set(-1, -17, CellState::Alive);
+        sw.set(40, 3, CellState::Dying(0));
+        let mut cells: Vec<_> = sw.cells().collect();
+        cells.sort_by_key(|&(x, y, _)| (x, y));
+        assert_eq!(
+            cells,
+            vec![(-1, -17, CellState::Alive), (40, 3, CellState::Dying(0))]
+        );
+    }
+
+    #[test]
+    fn test_get_set_negative_coordinates() {
+        let mut sw = SparseWorld::new(viewport(0, 0, 1, 1));
+        sw.set(-1, -17, CellState::Alive);
+        sw.set(-40, 3, CellState::Dying(0));
+        assert_eq!(sw.get(-1, -17), CellState::Alive);
+        assert_eq!(sw.get(-40, 3), CellState::Dying(0));
+        assert_eq!(sw.get(0, 0), CellState::Dead);
+
+        sw.set(-1, -17, CellState::Dead);
+        sw.set(-40, 3, CellState::Dead);
+        assert!(sw.tiles.is_empty());
+    }
+
+    #[test]
+    fn test_same_evolution_as_bounded_world_far_from_edges() {
+        let mut ws = WorldState::new(120);
+        ws.spawn_wick3(60, 60).unwrap();
+        ws.spawn_osc3(10, 10).unwrap();
+        let mut sw = SparseWorld::from(&ws);
+        for _ in 0..30 {
+            ws.next();
+            sw.next();
+            assert_eq!(sw.to_string(), ws.to_string());
+        }
+    }
+
+    #[test]
+    fn test_same_evolution_for_other_neighbourhoods() {
+        for (rule, neighbourhood) in [
+            ("B2/S/C3", "hexagonal"),
+            ("B1/S/C3", "von-neumann"),
+            ("B4-6/S5-8/C4", "moore:2"),
+            ("B2/S/C3", "custom:-3,0;0,3;2,-2"),
+        ] {
+            let mut ws = WorldState::new(100);
+            ws.set_rule(rule.parse().unwrap());
+            ws.set_neighbourhood(neighbourhood.parse().unwrap());
+            ws.spawn_wick3(50, 50).unwrap();
+            ws.spawn_glider4_downward(50, 40).unwrap();
+            let mut sw = SparseWorld::from(&ws);
+            for _ in 0..10 {
+                ws.next();
+                sw.next();
+                assert_eq!(sw.to_string(), ws.to_string());
+            }
+        }
+    }
+
+    #[test]
+    fn test_neighbourhood_farther_than_a_tile() {
+        for neighbourhood in ["moore:20", "custom:-35,0;0,18;17,-18"] {
+            let mut ws = WorldState::new(160);
+            ws.set_rule("B1-2/S/C3".parse().unwrap());
+            ws.set_neighbourhood(neighbourhood.parse().unwrap());
+            ws.spawn_osc3(80, 80).unwrap();
+            let mut sw = SparseWorld::from(&ws);
+            for _ in 0..3 {
+                ws.next();
+                sw.next();
+                assert_eq!(sw.to_string(), ws.to_string());
+            }
+        }
+    }
+
+    #[test]
+    fn test_glider_travels_past_the_viewport() {
+        let mut sw = SparseWorld::new(viewport(0, 0, 10, 10));
+        sw.spawn_glider4_upward(4, 4).unwrap();
+        for _ in 0..1_000 {
+            sw.next();
+        }
+        assert_eq!(sw.get(4, -996), CellState::Alive);
+        assert_eq!(sw.get(5, -996), CellState::Alive);
+        assert_eq!(sw.get(4, -995), CellState::Dying(0));
+        assert_eq!(sw.get(5, -995), CellState::Dying(0));
+        assert_eq!(sw.to_string(), "..........\n".repeat(10));
+        assert!(sw.as_vertices().is_empty());
+        assert!(!sw.is_extinct());
+        assert_eq!(sw.tiles.len(), 1);
+    }
+
+    #[test]
+    fn test_display_through_viewport() {
+        let mut sw = SparseWorld::new(viewport(-2, -1, 3, 2));
+        sw.set(-2, -1, CellState::Alive);
+        sw.set(0, 0, CellState::Dying(0));
+        sw.set(1, 0, CellState::Alive);
+        assert_eq!(sw.to_string(), "O..\n..X\n");
+    }
+
+    #[test]
+    fn test_as_vertices_through_viewport() {
+        let mut sw = SparseWorld::new(viewport(-5, -5, 1, 1));
+        sw.set(-5, -5, CellState::Alive);
+        sw.set(0, 0, CellState::Alive);
+        let cells = sw.as_vertices();
+        assert_eq!(cells.len(), 6);
+        assert!(cells.contains(&Vertex {
+            position: [-1.0, -1.0],
+            color: ALIVE_COLOR
+        }));
+    }
+}
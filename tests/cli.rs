@@ -0,0 +1,90 @@
+use std::process::Command;
+
+/// Run the program with `args`, and return its standard output.
+fn run(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_brian-s-brain"))
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Run the program with `args`, expecting it to fail, and return its
+/// standard error.
+fn fail(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_brian-s-brain"))
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(!output.status.success(), "{output:?}");
+    String::from_utf8(output.stderr).unwrap()
+}
+
+#[test]
+fn test_stats_on_the_standard_output() {
+    // the random seed is printed, but not on the standard output.
+    let csv = run(&["--stats", "-", "-i", "2", "-s", "5"]);
+    assert!(csv.starts_with("generation,alive,dying,dead,"), "{csv}");
+    assert_eq!(csv.lines().count(), 4);
+
+    let json = run(&[
+        "--stats",
+        "-",
+        "--stats-format=json-lines",
+        "-i",
+        "2",
+        "-s",
+        "5",
+    ]);
+    assert!(json.starts_with(r#"{"generation":0,"#), "{json}");
+}
+
+#[test]
+fn test_damage_on_the_standard_output() {
+    let csv = run(&["-i", "2", "-s", "5", "damage", "-o", "-"]);
+    assert!(csv.starts_with("generation,distance,"), "{csv}");
+}
+
+#[test]
+fn test_jump_errors() {
+    let error = fail(&["--example=1", "-j", "10", "-t", "torus", "--cli", "-i", "0"]);
+    assert!(error.contains("Torus topology"), "{error}");
+    let error = fail(&[
+        "--example=1",
+        "-j",
+        "18446744073709551615",
+        "--cli",
+        "-i",
+        "0",
+    ]);
+    assert!(
+        error.contains("a jump over 18446744073709551615"),
+        "{error}"
+    );
+    run(&[
+        "--example=1",
+        "-j",
+        "10",
+        "-t",
+        "torus",
+        "-u",
+        "--cli",
+        "-i",
+        "0",
+    ]);
+}
+
+#[test]
+fn test_options_take_precedence_over_the_scenario() {
+    let scenario = concat!(env!("CARGO_MANIFEST_DIR"), "/scenarios/example1.json");
+    let stats = |args: &[&str]| {
+        let args = [&["--scenario", scenario, "--stats", "-", "-i", "0"], args].concat();
+        run(&args).lines().nth(1).unwrap().to_string()
+    };
+    assert!(stats(&[]).starts_with("0,20,20,156,"));
+    let resized = stats(&["--width", "16", "--height", "15", "--init", "tile:."]);
+    assert!(resized.starts_with("0,20,20,200,"), "{resized}");
+    let filled = stats(&["--size", "20", "--randomness", "1"]);
+    assert!(filled.starts_with("0,380,20,0,"), "{filled}");
+}
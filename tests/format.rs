@@ -0,0 +1,30 @@
+use brian_s_brain::format::Format;
+use brian_s_brain::world_state::{CellState, WorldState};
+
+use std::path::Path;
+
+#[test]
+fn test_format_of_path() {
+    assert_eq!(Format::of(Path::new("glider.rle")), Format::Rle);
+    assert_eq!(Format::of(Path::new("glider.cells")), Format::Plaintext);
+    assert_eq!(Format::of(Path::new("glider.mc")), Format::Macrocell);
+    assert_eq!(Format::of(Path::new("glider")), Format::Rle);
+}
+
+#[test]
+fn test_round_trip_every_format() {
+    let mut ws = WorldState::example2();
+    ws.set_rule("B2/S/C4".parse().unwrap());
+    ws.set(0, 0, CellState::Dying(1)).unwrap();
+    for format in [Format::Rle, Format::Plaintext, Format::Macrocell] {
+        let parsed = format.parse(&format.write(&ws)).unwrap();
+        assert_eq!(parsed, ws, "{format:?}");
+    }
+}
+
+#[test]
+fn test_malformed_files_are_rejected() {
+    for format in [Format::Rle, Format::Plaintext, Format::Macrocell] {
+        assert!(format.parse("x = 1, y = 1\n?!\n").is_err(), "{format:?}");
+    }
+}
@@ -0,0 +1,148 @@
+use brian_s_brain::rule::Rule;
+use brian_s_brain::topology::Topology;
+use brian_s_brain::world_state::{CellState, OutOfBounds, Spawn, WorldState};
+
+#[test]
+fn test_get_set() {
+    let mut ws = WorldState::with_dimensions(4, 3);
+    assert_eq!(ws.get(3, 2), Ok(CellState::Dead));
+    ws.set(3, 2, CellState::Alive).unwrap();
+    ws.set(0, 1, CellState::Dying(0)).unwrap();
+    assert_eq!(ws.get(3, 2), Ok(CellState::Alive));
+    assert_eq!(ws.get(0, 1), Ok(CellState::Dying(0)));
+    assert_eq!(ws.to_string(), "....\nX...\n...O\n");
+}
+
+#[test]
+fn test_get_set_outside_the_world() {
+    let mut ws = WorldState::with_dimensions(4, 3);
+    assert_eq!(ws.get(4, 0), Err(OutOfBounds { x: 4, y: 0 }));
+    assert_eq!(
+        ws.set(0, 3, CellState::Alive),
+        Err(OutOfBounds { x: 0, y: 3 })
+    );
+    assert_eq!(
+        ws.spawn_glider4_downward(3, 0),
+        Err(OutOfBounds { x: 4, y: 0 })
+    );
+    assert_eq!(ws, WorldState::with_dimensions(4, 3));
+}
+
+#[test]
+fn test_population() {
+    let mut ws = WorldState::with_dimensions(4, 3);
+    ws.spawn_wick3(0, 0).unwrap();
+    assert_eq!(ws.population(CellState::Alive), 5);
+    assert_eq!(ws.population(CellState::Dying(0)), 1);
+    assert_eq!(ws.population(CellState::Dead), 6);
+}
+
+#[test]
+fn test_iter_row_by_row() {
+    let mut ws = WorldState::with_dimensions(2, 2);
+    ws.set(1, 0, CellState::Alive).unwrap();
+    let cells: Vec<_> = ws.iter().collect();
+    assert_eq!(
+        cells,
+        vec![
+            (0, 0, CellState::Dead),
+            (1, 0, CellState::Alive),
+            (0, 1, CellState::Dead),
+            (1, 1, CellState::Dead),
+        ]
+    );
+}
+
+#[test]
+fn test_spawned_glider_moves_down() {
+    let mut ws = WorldState::new(10);
+    ws.spawn_glider4_downward(4, 2).unwrap();
+    for _ in 0..4 {
+        ws.next();
+    }
+    let alive: Vec<_> = ws
+        .iter()
+        .filter(|&(_, _, cell)| cell == CellState::Alive)
+        .map(|(x, y, _)| (x, y))
+        .collect();
+    assert_eq!(alive, vec![(4, 7), (5, 7)]);
+}
+
+#[test]
+fn test_brian_s_brain_rule() {
+    let mut ws = WorldState::new(3);
+    ws.set(0, 0, CellState::Alive).unwrap();
+    ws.set(2, 0, CellState::Alive).unwrap();
+    ws.next();
+    assert_eq!(ws.to_string(), "XOX\n.O.\n...\n");
+    ws.next();
+    assert_eq!(ws.to_string(), ".X.\nOXO\n...\n");
+}
+
+#[test]
+fn test_other_rule_and_topology() {
+    // a blinker of the Game of Life, across the top and bottom edges.
+    let mut ws: WorldState = "..O..\n..O..\n.....\n.....\n.....\n".parse().unwrap();
+    ws.set_rule("B3/S23".parse::<Rule>().unwrap());
+    ws.set_topology(Topology::Torus);
+    ws.set(2, 4, CellState::Alive).unwrap();
+    assert_eq!(ws.rule().to_string(), "B3/S23/C2");
+    let start = ws.clone();
+    ws.next();
+    assert_eq!(ws.to_string(), ".OOO.\n.....\n.....\n.....\n.....\n");
+    ws.next();
+    assert_eq!(ws, start);
+}
+
+#[test]
+fn test_seeded_brian_s_brain_golden() {
+    let mut ws = WorldState::with_dimensions(10, 6);
+    ws.randomize_with_seed(0.3, 2023).unwrap();
+    assert_eq!(
+        ws.to_string(),
+        "..O.O..O.O\nO.O..O....\n.O...O.OO.\n.O....O.O.\nOO......O.\n...O......\n"
+    );
+    for _ in 0..3 {
+        ws.next();
+    }
+    assert_eq!(
+        ws.to_string(),
+        ".......O..\n....XOXX..\n...O..OOO.\n...OX....X\n..XO......\n..........\n"
+    );
+}
+
+#[test]
+fn test_seeded_life_golden() {
+    let mut ws = WorldState::new(8);
+    ws.set_rule("B3/S23".parse().unwrap());
+    ws.randomize_with_seed(0.4, 42).unwrap();
+    assert_eq!(
+        ws.to_string(),
+        "O.OOO...\n..O..O.O\n..O..OO.\n..OOO.OO\n.....O..\n.O.OO...\n..OO....\nO.O.O..O\n"
+    );
+    for _ in 0..4 {
+        ws.next();
+    }
+    assert_eq!(
+        ws.to_string(),
+        "..OOOO..\n.O..OO..\nOOO..O..\nOO......\n.OOO..O.\n.OO.OO..\n...OO...\n........\n"
+    );
+}
+
+#[test]
+fn test_replay_recorded_diffs() {
+    let mut ws = WorldState::example2();
+    let first = ws.clone();
+    let mut diffs = vec![];
+    for _ in 0..10 {
+        let previous = ws.clone();
+        ws.next();
+        diffs.push(previous.diff(&ws).unwrap());
+    }
+
+    let mut replayed = first;
+    for diff in &diffs {
+        replayed.apply_diff(diff).unwrap();
+    }
+    assert_eq!(replayed, ws);
+}